@@ -0,0 +1,207 @@
+use crate::error::Error;
+use crate::locale::Locale;
+use crate::profile::{select_text, selector};
+use scraper::ElementRef;
+use strum::{Display, EnumIter, EnumString};
+use tracing::{event, Level};
+
+/// A character attribute from the parameter section of the profile page.
+#[derive(Debug, Display, EnumString, EnumIter, Eq, Hash, PartialEq, Clone, Copy)]
+#[strum(serialize_all = "title_case")]
+pub enum Attribute {
+    // base attributes
+    Strength,
+    Dexterity,
+    Vitality,
+    Intelligence,
+    Mind,
+    // offensive and defensive properties
+    CriticalHitRate,
+    Determination,
+    DirectHitRate,
+    Defense,
+    MagicDefense,
+    AttackPower,
+    SkillSpeed,
+    AttackMagicPotency,
+    HealingMagicPotency,
+    SpellSpeed,
+    Tenacity,
+    Piety,
+    // disciples of hand
+    Craftsmanship,
+    Control,
+    #[strum(serialize = "CP")]
+    Cp,
+    // disciples of land
+    Gathering,
+    Perception,
+    #[strum(serialize = "GP")]
+    Gp,
+}
+
+/// Attributes of a character, for the job they had equipped when the profile
+/// was captured. Attributes which don't apply to that job are `None`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Attributes {
+    pub strength: Option<u64>,
+    pub dexterity: Option<u64>,
+    pub vitality: Option<u64>,
+    pub intelligence: Option<u64>,
+    pub mind: Option<u64>,
+    pub critical_hit_rate: Option<u64>,
+    pub determination: Option<u64>,
+    pub direct_hit_rate: Option<u64>,
+    pub defense: Option<u64>,
+    pub magic_defense: Option<u64>,
+    pub attack_power: Option<u64>,
+    pub skill_speed: Option<u64>,
+    pub attack_magic_potency: Option<u64>,
+    pub healing_magic_potency: Option<u64>,
+    pub spell_speed: Option<u64>,
+    pub tenacity: Option<u64>,
+    pub piety: Option<u64>,
+    pub craftsmanship: Option<u64>,
+    pub control: Option<u64>,
+    pub cp: Option<u64>,
+    pub gathering: Option<u64>,
+    pub perception: Option<u64>,
+    pub gp: Option<u64>,
+}
+
+impl Attributes {
+    fn field(&mut self, attribute: Attribute) -> &mut Option<u64> {
+        match attribute {
+            Attribute::Strength => &mut self.strength,
+            Attribute::Dexterity => &mut self.dexterity,
+            Attribute::Vitality => &mut self.vitality,
+            Attribute::Intelligence => &mut self.intelligence,
+            Attribute::Mind => &mut self.mind,
+            Attribute::CriticalHitRate => &mut self.critical_hit_rate,
+            Attribute::Determination => &mut self.determination,
+            Attribute::DirectHitRate => &mut self.direct_hit_rate,
+            Attribute::Defense => &mut self.defense,
+            Attribute::MagicDefense => &mut self.magic_defense,
+            Attribute::AttackPower => &mut self.attack_power,
+            Attribute::SkillSpeed => &mut self.skill_speed,
+            Attribute::AttackMagicPotency => &mut self.attack_magic_potency,
+            Attribute::HealingMagicPotency => &mut self.healing_magic_potency,
+            Attribute::SpellSpeed => &mut self.spell_speed,
+            Attribute::Tenacity => &mut self.tenacity,
+            Attribute::Piety => &mut self.piety,
+            Attribute::Craftsmanship => &mut self.craftsmanship,
+            Attribute::Control => &mut self.control,
+            Attribute::Cp => &mut self.cp,
+            Attribute::Gathering => &mut self.gathering,
+            Attribute::Perception => &mut self.perception,
+            Attribute::Gp => &mut self.gp,
+        }
+    }
+
+    pub fn get(&self, attribute: Attribute) -> Option<u64> {
+        let mut attributes = *self;
+        *attributes.field(attribute)
+    }
+
+    pub fn set(&mut self, attribute: Attribute, value: u64) {
+        *self.field(attribute) = Some(value);
+    }
+}
+
+/// Parse the attribute tables of a profile page. Attributes this version
+/// doesn't know about are skipped.
+pub fn parse(profile: ElementRef, locale: &Locale) -> Result<Attributes, Error> {
+    const ROW: &str = "table.character__param__list tr";
+    const NAME: &str = "th";
+    const VALUE: &str = "td";
+
+    let mut attributes = Attributes::default();
+    for row in profile.select(&selector(ROW)?) {
+        let name = select_text(row, NAME, "attribute name")?;
+        // a new or renamed stat shouldn't lose the rest of the profile
+        let Some(attribute) = locale.attribute(&name) else {
+            event!(Level::WARN, "skipping unknown attribute {name:?}");
+            continue;
+        };
+        let value = select_text(row, VALUE, "attribute value")?;
+        let value =
+            value
+                .trim()
+                .replace(',', "")
+                .parse::<u64>()
+                .map_err(|_| Error::InvalidElement {
+                    field: "attribute value",
+                    selector: ROW,
+                    value,
+                })?;
+        attributes.set(attribute, value);
+    }
+
+    // crafters and gatherers show CP or GP alongside HP, instead of MP
+    for (attribute, css) in [(Attribute::Cp, locale.cp), (Attribute::Gp, locale.gp)] {
+        if let Some(element) = profile.select(&selector(css)?).next() {
+            let text = element.text().collect::<String>();
+            let value = text
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| Error::InvalidElement {
+                    field: "attribute value",
+                    selector: css,
+                    value: text.clone(),
+                })?;
+            attributes.set(attribute, value);
+        }
+    }
+    Ok(attributes)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::locale::Language;
+    use scraper::Html;
+
+    #[test]
+    fn parse_attributes() -> Result<(), Error> {
+        let html = Html::parse_document(
+            r#"
+            <table class="character__param__list">
+              <tr><th><span>Strength</span></th><td>5,021</td></tr>
+              <tr><th><span>Dexterity</span></th><td>415</td></tr>
+              <tr><th><span>Some Future Stat</span></th><td>100</td></tr>
+            </table>
+            <table class="character__param__list">
+              <tr><th><span>Critical Hit Rate</span></th><td>3102</td></tr>
+              <tr><th><span>Tenacity</span></th><td>868</td></tr>
+            </table>
+            "#,
+        );
+        let attributes = parse(html.root_element(), Language::English.locale())?;
+        assert_eq!(
+            attributes,
+            Attributes {
+                strength: Some(5021),
+                dexterity: Some(415),
+                critical_hit_rate: Some(3102),
+                tenacity: Some(868),
+                ..Attributes::default()
+            }
+        );
+        assert_eq!(attributes.get(Attribute::Tenacity), Some(868));
+        assert_eq!(attributes.get(Attribute::Piety), None);
+
+        let german = Html::parse_document(
+            r#"
+            <table class="character__param__list">
+              <tr><th>Kunstfertigkeit</th><td>5000</td></tr>
+            </table>
+            <p class="character__param__text__cp--de">CP</p><span>600</span>
+            "#,
+        );
+        let attributes = parse(german.root_element(), Language::German.locale())?;
+        assert_eq!(attributes.craftsmanship, Some(5000));
+        assert_eq!(attributes.cp, Some(600));
+
+        Ok(())
+    }
+}
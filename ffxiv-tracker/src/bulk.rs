@@ -0,0 +1,117 @@
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::db::{SnapshotOutcome, TrackerDatabase};
+use crate::error::Error;
+use crate::profile::Profile;
+use crate::region::Region;
+use tracing::{event, info_span, Level};
+
+/// Limits on how hard to hit lodestone while snapshotting many characters.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimit {
+    /// Most profiles downloaded at once.
+    pub concurrency: usize,
+    /// Least time between starting one download and the next.
+    pub delay: Duration,
+}
+
+/// Spaces out requests shared between worker threads.
+struct Throttle {
+    next: Mutex<Instant>,
+    delay: Duration,
+}
+
+impl Throttle {
+    /// Block until this thread may start a request.
+    fn wait(&self) {
+        let start = {
+            let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+            let start = (*next).max(Instant::now());
+            *next = start + self.delay;
+            start
+        };
+        thread::sleep(start.saturating_duration_since(Instant::now()));
+    }
+}
+
+/// Snapshot every character in `user_ids`, returning the ones which failed.
+///
+/// Profiles are downloaded on up to `limit.concurrency` threads, but written
+/// to the database one at a time as they arrive.
+pub fn snapshot_characters(
+    database: &mut TrackerDatabase,
+    user_ids: &[u64],
+    region: Region,
+    limit: RateLimit,
+) -> Vec<(u64, Error)> {
+    let queue = Mutex::new(user_ids.iter().copied());
+    let throttle = Throttle {
+        next: Mutex::new(Instant::now()),
+        delay: limit.delay,
+    };
+    let (sender, receiver) = mpsc::channel::<(u64, Result<Profile, Error>)>();
+
+    let mut failures: Vec<(u64, Error)> = vec![];
+    thread::scope(|scope| {
+        for _ in 0..limit.concurrency.clamp(1, user_ids.len().max(1)) {
+            let sender = sender.clone();
+            let (queue, throttle) = (&queue, &throttle);
+            scope.spawn(move || loop {
+                let next = queue.lock().unwrap_or_else(|e| e.into_inner()).next();
+                let Some(user_id) = next else {
+                    break;
+                };
+                throttle.wait();
+                let profile = info_span!("fetch", id = user_id).in_scope(|| {
+                    event!(Level::INFO, "downloading profile");
+                    Profile::get(user_id, region)
+                });
+                if sender.send((user_id, profile)).is_err() {
+                    break;
+                }
+            });
+        }
+        // the receiver stops once every worker has dropped its sender
+        drop(sender);
+
+        for (user_id, profile) in receiver {
+            let _guard = info_span!("character", id = user_id).entered();
+            match profile.and_then(|profile| database.snapshot(profile)) {
+                Ok(SnapshotOutcome::Created(snapshot_id)) => {
+                    event!(Level::INFO, "created snapshot {snapshot_id}")
+                }
+                Ok(SnapshotOutcome::Unchanged(snapshot_id)) => {
+                    event!(Level::INFO, "unchanged since snapshot {snapshot_id}")
+                }
+                Err(e) => {
+                    event!(Level::ERROR, "snapshot failed: {e}");
+                    failures.push((user_id, e));
+                }
+            }
+        }
+    });
+    failures
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn throttle_spaces_requests() {
+        let throttle = Throttle {
+            next: Mutex::new(Instant::now()),
+            delay: Duration::from_millis(20),
+        };
+        let start = Instant::now();
+        thread::scope(|scope| {
+            for _ in 0..3 {
+                scope.spawn(|| throttle.wait());
+            }
+        });
+        // the first request starts immediately, the others wait their turn
+        assert!(start.elapsed() >= Duration::from_millis(40));
+    }
+}
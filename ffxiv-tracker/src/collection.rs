@@ -0,0 +1,260 @@
+use crate::error::Error;
+use crate::profile::{fetch, has_next_page, link_id, select_attr, select_text, selector};
+use crate::region::Region;
+use chrono::{DateTime, Utc};
+use scraper::Html;
+use strum::{Display, EnumString};
+
+/// Most achievement list pages to follow, at 50 achievements to a page.
+const MAX_ACHIEVEMENT_PAGES: usize = 100;
+
+/// Collectables listed on their own page of a character's profile.
+#[derive(Debug, Display, EnumString, Eq, Hash, PartialEq, Clone, Copy, PartialOrd, Ord)]
+#[strum(serialize_all = "title_case")]
+pub enum Collectable {
+    Mount,
+    Minion,
+}
+
+/// An achievement a character has earned.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Achievement {
+    pub achievement_id: u64,
+    pub name: String,
+    pub points: u64,
+    /// When the achievement was earned, as shown on lodestone.
+    pub earned_at: Option<DateTime<Utc>>,
+}
+
+/// A character's achievements, mounts and minions, collected from their
+/// lodestone pages.
+///
+/// Names are as shown on the lodestone site the collection was fetched from.
+#[derive(Debug)]
+pub struct Collection {
+    pub user_id: u64,
+    /// When the collection was downloaded from lodestone.
+    pub captured_at: DateTime<Utc>,
+    /// Empty if the character's achievements are private.
+    pub achievements: Vec<Achievement>,
+    /// Total achievement points, or `None` if achievements are private.
+    pub achievement_points: Option<u64>,
+    pub mounts: Vec<String>,
+    pub minions: Vec<String>,
+}
+
+impl Collection {
+    pub fn get(user_id: u64, region: Region) -> Result<Collection, Error> {
+        let base_url = format!("{}/{user_id}", region.character_url());
+        let captured_at = Utc::now();
+
+        let mut achievements: Vec<Achievement> = vec![];
+        let mut achievement_points: Option<u64> = None;
+        for page_number in 1..=MAX_ACHIEVEMENT_PAGES {
+            let page = match fetch(
+                &format!("{base_url}/achievement/"),
+                &[("page", &page_number.to_string())],
+            ) {
+                Ok(page) => page,
+                // characters can hide their achievements
+                Err(Error::Http { status: 403, .. }) => break,
+                Err(e) => Err(e)?,
+            };
+            if page_number == 1 {
+                achievement_points = parse_achievement_points(&page)?;
+            }
+            let (page_achievements, more) = parse_achievements(&page)?;
+            achievements.extend(page_achievements);
+            if !more {
+                break;
+            }
+        }
+
+        let mounts = parse_collectables(
+            &fetch(&format!("{base_url}/mount/"), &[])?,
+            Collectable::Mount,
+        )?;
+        let minions = parse_collectables(
+            &fetch(&format!("{base_url}/minion/"), &[])?,
+            Collectable::Minion,
+        )?;
+
+        Ok(Collection {
+            user_id,
+            captured_at,
+            achievements,
+            achievement_points,
+            mounts,
+            minions,
+        })
+    }
+
+    /// Names of the collectables of one kind.
+    pub fn collectables(&self, kind: Collectable) -> &[String] {
+        match kind {
+            Collectable::Mount => &self.mounts,
+            Collectable::Minion => &self.minions,
+        }
+    }
+}
+
+/// Parse one page of a character's achievement list, and whether there are
+/// more pages after it.
+fn parse_achievements(page: &Html) -> Result<(Vec<Achievement>, bool), Error> {
+    const ENTRY: &str = "div.ldst__achievement li.entry";
+    const LINK: &str = "a.entry__achievement";
+    const TEXT: &str = "p.entry__activity__txt";
+    const POINTS: &str = "p.entry__achievement__number";
+    const TIME: &str = "time.entry__activity__time";
+
+    let mut achievements: Vec<Achievement> = vec![];
+    for entry in page.select(&selector(ENTRY)?) {
+        let href = select_attr(entry, LINK, "href", "achievement link")?;
+        let achievement_id = link_id(&href).ok_or(Error::InvalidElement {
+            field: "achievement link",
+            selector: LINK,
+            value: href.clone(),
+        })?;
+        let points = select_text(entry, POINTS, "achievement points")?;
+        let points = points
+            .trim()
+            .parse::<u64>()
+            .map_err(|_| Error::InvalidElement {
+                field: "achievement points",
+                selector: POINTS,
+                value: points,
+            })?;
+        // the time is filled in by a script, from a unix timestamp
+        let earned_at = entry
+            .select(&selector(TIME)?)
+            .next()
+            .and_then(|time| parse_timestamp(&time.inner_html()));
+        achievements.push(Achievement {
+            achievement_id,
+            name: quoted_name(&select_text(entry, TEXT, "achievement name")?),
+            points,
+            earned_at,
+        });
+    }
+
+    let more = has_next_page(page)? && !achievements.is_empty();
+    Ok((achievements, more))
+}
+
+/// Total achievement points, shown above the achievement list unless the
+/// character's achievements are private.
+fn parse_achievement_points(page: &Html) -> Result<Option<u64>, Error> {
+    const POINTS: &str = "p.achievement__point";
+
+    Ok(page
+        .select(&selector(POINTS)?)
+        .next()
+        .map(|element| element.text().collect::<String>().replace(',', ""))
+        .and_then(|points| points.trim().parse::<u64>().ok()))
+}
+
+/// Parse the names of the mounts or minions on a collection page.
+fn parse_collectables(page: &Html, kind: Collectable) -> Result<Vec<String>, Error> {
+    const MOUNTS: &str = "div.character__mounts ul li";
+    const MINIONS: &str = "div.character__minion ul li";
+    // names are only shown in tooltips
+    const ICON: &str = "div.character__item_icon";
+
+    let css = match kind {
+        Collectable::Mount => MOUNTS,
+        Collectable::Minion => MINIONS,
+    };
+    let mut names: Vec<String> = vec![];
+    for item in page.select(&selector(css)?) {
+        let name = select_attr(item, ICON, "data-tooltip", "collectable name")?;
+        names.push(name.trim().to_string());
+    }
+    Ok(names)
+}
+
+/// The achievement name from an activity line, such as `Test Player earned
+/// the achievement "To Crush Your Enemies I"!`.
+fn quoted_name(text: &str) -> String {
+    const OPENING: &[char] = &['"', '“', '「', '«'];
+    const CLOSING: &[char] = &['"', '”', '」', '»'];
+
+    text.find(OPENING)
+        .and_then(|start| {
+            let rest = &text[start..];
+            let rest = &rest[rest.chars().next()?.len_utf8()..];
+            let end = rest.rfind(CLOSING)?;
+            Some(rest[..end].trim().to_string())
+        })
+        .unwrap_or_else(|| text.trim().to_string())
+}
+
+/// The unix timestamp in a lodestone time script, such as
+/// `ldst_strftime(1577836800, 'YMD')`.
+fn parse_timestamp(script: &str) -> Option<DateTime<Utc>> {
+    let (_, rest) = script.split_once("ldst_strftime(")?;
+    let seconds = rest.split(|c: char| !c.is_ascii_digit()).next()?;
+    DateTime::from_timestamp(seconds.parse().ok()?, 0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_collection() -> Result<(), Error> {
+        let achievements = Html::parse_document(
+            r#"
+            <div class="ldst__achievement">
+              <p class="achievement__point">1,234</p>
+              <ul>
+                <li class="entry">
+                  <a href="/lodestone/character/38598907/achievement/detail/1234/" class="entry__achievement">
+                    <div class="entry__achievement__frame"><img src="icon.png"></div>
+                    <p class="entry__activity__txt">Test Player earned the achievement "To Crush Your Enemies I"!</p>
+                    <p class="entry__achievement__number">10</p>
+                    <time class="entry__activity__time"><span id="datetime-0.1">-</span><script>document.getElementById('datetime-0.1').innerHTML = ldst_strftime(1577836800, 'YMD');</script></time>
+                  </a>
+                </li>
+              </ul>
+              <ul class="btn__pager">
+                <li><a href="https://na.finalfantasyxiv.com/lodestone/character/38598907/achievement/?page=2" class="btn__pager__next btn__pager__no"></a></li>
+              </ul>
+            </div>
+            "#,
+        );
+        let (parsed, more) = parse_achievements(&achievements)?;
+        assert!(!more);
+        assert_eq!(parse_achievement_points(&achievements)?, Some(1234));
+        assert_eq!(
+            parsed,
+            vec![Achievement {
+                achievement_id: 1234,
+                name: "To Crush Your Enemies I".to_string(),
+                points: 10,
+                earned_at: DateTime::from_timestamp(1577836800, 0),
+            }]
+        );
+        assert_eq!(
+            quoted_name("「ちょっと一息」を達成しました！"),
+            "ちょっと一息"
+        );
+
+        let mounts = Html::parse_document(
+            r#"
+            <div class="character__mounts">
+              <ul>
+                <li><div class="character__item_icon" data-tooltip="Company Chocobo"><img src="a.png"></div></li>
+                <li><div class="character__item_icon" data-tooltip="Fat Chocobo"><img src="b.png"></div></li>
+              </ul>
+            </div>
+            "#,
+        );
+        assert_eq!(
+            parse_collectables(&mounts, Collectable::Mount)?,
+            vec!["Company Chocobo".to_string(), "Fat Chocobo".to_string()]
+        );
+        assert!(parse_collectables(&mounts, Collectable::Minion)?.is_empty());
+
+        Ok(())
+    }
+}
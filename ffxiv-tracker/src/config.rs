@@ -0,0 +1,223 @@
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::error::Error;
+use crate::region::Region;
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+
+/// Tracker configuration, usually kept in `tracker.kdl`:
+///
+/// ```kdl
+/// database "./ffxiv-tracker.sqlite"
+///
+/// character 38598907 nickname="yov" region="na" interval="1h"
+/// character 26351638
+/// ```
+#[derive(Debug, knuffel::Decode)]
+pub struct Config {
+    #[knuffel(child, unwrap(argument), default = PathBuf::from("./ffxiv-tracker.sqlite"))]
+    pub database: PathBuf,
+    #[knuffel(children(name = "character"))]
+    pub characters: Vec<Character>,
+}
+
+/// A character on the watchlist.
+#[derive(Debug, knuffel::Decode)]
+pub struct Character {
+    /// Lodestone character ID.
+    #[knuffel(argument)]
+    pub id: u64,
+    #[knuffel(property)]
+    pub nickname: Option<String>,
+    /// Lodestone site to fetch the character from.
+    #[knuffel(property, str, default)]
+    pub region: Region,
+    /// Minimum time between snapshots of this character.
+    #[knuffel(property, str)]
+    pub interval: Option<Interval>,
+}
+
+impl Character {
+    /// Name to refer to the character by in logs and summaries.
+    pub fn label(&self) -> String {
+        match &self.nickname {
+            Some(nickname) => format!("{nickname} ({})", self.id),
+            None => self.id.to_string(),
+        }
+    }
+
+    /// The character as a line of `tracker.kdl`.
+    pub fn to_kdl(&self) -> String {
+        let mut line = format!("character {}", self.id);
+        if let Some(nickname) = &self.nickname {
+            let escaped = nickname.replace('\\', "\\\\").replace('"', "\\\"");
+            line += &format!(" nickname=\"{escaped}\"");
+        }
+        if self.region != Region::default() {
+            line += &format!(" region=\"{}\"", self.region);
+        }
+        if let Some(interval) = self.interval {
+            line += &format!(" interval=\"{interval}\"");
+        }
+        line
+    }
+}
+
+/// A polling interval, written as a positive number followed by a unit: `30s`,
+/// `15m`, `6h` or `1d`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interval(pub TimeDelta);
+
+impl FromStr for Interval {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let split = input
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or(format!("missing unit in interval: {input}"))?;
+        let (amount, unit) = input.split_at(split);
+        let amount = amount
+            .parse::<i64>()
+            .map_err(|e| format!("invalid interval {input}: {e}"))?;
+        // a zero interval would poll lodestone continuously
+        if amount <= 0 {
+            Err(format!("interval must be positive: {input}"))?;
+        }
+        let delta = match unit {
+            "s" => TimeDelta::try_seconds(amount),
+            "m" => TimeDelta::try_minutes(amount),
+            "h" => TimeDelta::try_hours(amount),
+            "d" => TimeDelta::try_days(amount),
+            _ => Err(format!("unknown unit in interval: {input}"))?,
+        };
+        delta
+            .map(Interval)
+            .ok_or(format!("interval out of range: {input}"))
+    }
+}
+
+impl fmt::Display for Interval {
+    /// Formats in the largest unit which divides the interval evenly.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let seconds = self.0.num_seconds();
+        match seconds {
+            _ if seconds % 86_400 == 0 => write!(f, "{}d", seconds / 86_400),
+            _ if seconds % 3_600 == 0 => write!(f, "{}h", seconds / 3_600),
+            _ if seconds % 60 == 0 => write!(f, "{}m", seconds / 60),
+            _ => write!(f, "{seconds}s"),
+        }
+    }
+}
+
+/// Parse a timestamp given by the user, as RFC 3339 or a date. Dates are taken
+/// as midnight UTC.
+pub fn parse_time(input: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(input) {
+        return Ok(time.to_utc());
+    }
+    let date = NaiveDate::parse_from_str(input, "%Y-%m-%d")
+        .map_err(|_| format!("expected RFC 3339 timestamp or YYYY-MM-DD date: {input}"))?;
+    Ok(date.and_time(Default::default()).and_utc())
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Config, Error> {
+        let text = fs::read_to_string(path).map_err(|source| Error::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::parse(&path.display().to_string(), &text)
+    }
+
+    /// Append a character to the config at `path`, creating it if needed.
+    ///
+    /// The file is appended to rather than rewritten, so comments and
+    /// formatting are kept.
+    pub fn add_character(path: &Path, character: &Character) -> Result<(), Error> {
+        let io_error = |source| Error::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+        let mut text = String::new();
+        if path.exists() {
+            let existing = fs::read_to_string(path).map_err(io_error)?;
+            let config = Self::parse(&path.display().to_string(), &existing)?;
+            if config.characters.iter().any(|c| c.id == character.id) {
+                Err(Error::Config(format!(
+                    "{} is already tracked",
+                    character.id
+                )))?;
+            }
+            if !existing.is_empty() && !existing.ends_with('\n') {
+                text.push('\n');
+            }
+        }
+        text += &character.to_kdl();
+        text.push('\n');
+
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| file.write_all(text.as_bytes()))
+            .map_err(io_error)
+    }
+
+    fn parse(file_name: &str, text: &str) -> Result<Config, Error> {
+        knuffel::parse::<Config>(file_name, text).map_err(|e| Error::Config(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_config() -> Result<(), Error> {
+        let mut config = Config::parse(
+            "tracker.kdl",
+            r#"
+            database "./tracked.sqlite"
+            character 38598907 nickname="yov" region="eu" interval="90m"
+            character 26351638
+            "#,
+        )?;
+
+        assert_eq!(config.database, PathBuf::from("./tracked.sqlite"));
+        assert_eq!(config.characters.len(), 2);
+        assert_eq!(config.characters[0].label(), "yov (38598907)");
+        assert_eq!(config.characters[0].region, Region::Eu);
+        assert_eq!(
+            config.characters[0].interval,
+            Some(Interval(TimeDelta::minutes(90)))
+        );
+        assert_eq!(config.characters[1].label(), "26351638");
+        assert_eq!(config.characters[1].interval, None);
+        assert_eq!(config.characters[1].region, Region::Na);
+
+        let defaults = Config::parse("tracker.kdl", "")?;
+        assert_eq!(defaults.database, PathBuf::from("./ffxiv-tracker.sqlite"));
+
+        let line = Character {
+            nickname: Some("\"yov\"".to_string()),
+            ..config.characters.remove(0)
+        }
+        .to_kdl();
+        assert_eq!(
+            line,
+            r#"character 38598907 nickname="\"yov\"" region="eu" interval="90m""#
+        );
+        let reparsed = Config::parse("tracker.kdl", &line)?;
+        assert_eq!(reparsed.characters[0].nickname.as_deref(), Some("\"yov\""));
+
+        assert!("15".parse::<Interval>().is_err());
+        assert!("15w".parse::<Interval>().is_err());
+        assert!("0s".parse::<Interval>().is_err());
+        assert!("-1m".parse::<Interval>().is_err());
+
+        Ok(())
+    }
+}
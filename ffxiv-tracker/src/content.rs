@@ -0,0 +1,128 @@
+use crate::error::Error;
+use crate::locale::Locale;
+use crate::profile::{parse_exp, parse_level, select_text, selector};
+use scraper::ElementRef;
+use std::collections::BTreeMap;
+use strum::{Display, EnumIter, EnumString};
+
+/// Special content with its own progression, levelled separately from jobs.
+///
+/// Names on the class/job page describe the progression rather than the
+/// content, so those are accepted when parsing.
+#[derive(
+    Debug, Display, EnumString, EnumIter, Eq, Hash, PartialEq, Clone, Copy, PartialOrd, Ord,
+)]
+pub enum Content {
+    /// Elemental level, in the Forbidden Land, Eureka.
+    #[strum(to_string = "Eureka", serialize = "Elemental Level")]
+    Eureka,
+    /// Resistance rank, in the Bozjan Southern Front and Zadnor.
+    #[strum(to_string = "Bozja", serialize = "Resistance Rank")]
+    Bozja,
+}
+
+/// A snapshot of special content progression taken from a lodestone profile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentSnapshot {
+    pub content: Content,
+    pub level: Option<u64>,
+    pub exp: Option<(u64, u64)>,
+}
+
+/// Snapshot of all special content shown for a player.
+///
+/// Unlike jobs, content is only listed once a player has unlocked it.
+#[derive(Debug, Default)]
+pub struct PlayerContentSnapshot(pub BTreeMap<Content, ContentSnapshot>);
+
+/// Parse special content progression from a class/job page.
+///
+/// These entries share their markup with jobs, but sit outside the job lists.
+/// Entries for content this version doesn't know about are ignored.
+pub fn parse(jobs_page: ElementRef, locale: &Locale) -> Result<PlayerContentSnapshot, Error> {
+    const NAME: &str = "div.character__job__name";
+    const LEVEL: &str = "div.character__job__level";
+    const EXP: &str = "div.character__job__exp";
+    const JOB_LIST: &str = "character__job";
+
+    let mut snapshots: BTreeMap<Content, ContentSnapshot> = BTreeMap::new();
+    for name in jobs_page.select(&selector(NAME)?) {
+        let in_job_list = name.ancestors().any(|node| {
+            node.value().as_element().is_some_and(|element| {
+                element.name() == "ul" && element.classes().any(|class| class == JOB_LIST)
+            })
+        });
+        if in_job_list {
+            continue;
+        }
+        let Some(content) = locale.content(&name.text().collect::<String>()) else {
+            continue;
+        };
+        let Some(entry) = name.parent().and_then(ElementRef::wrap) else {
+            continue;
+        };
+        snapshots.insert(
+            content,
+            ContentSnapshot {
+                content,
+                level: parse_level(&select_text(entry, LEVEL, "content level")?),
+                exp: parse_exp(&select_text(entry, EXP, "content exp")?),
+            },
+        );
+    }
+    Ok(PlayerContentSnapshot(snapshots))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::locale::Language;
+    use scraper::Html;
+
+    #[test]
+    fn parse_content() -> Result<(), Error> {
+        let html = Html::parse_document(
+            r#"
+            <ul class="character__job">
+              <li>
+                <div class="character__job__level">90</div>
+                <div class="character__job__name">Paladin</div>
+                <div class="character__job__exp">1,000 / 2,000</div>
+              </li>
+            </ul>
+            <div class="character__job__list">
+              <div class="character__job__level">35</div>
+              <div class="character__job__name">Elemental Level</div>
+              <div class="character__job__exp">12,345 / 67,890</div>
+            </div>
+            <div class="character__job__list">
+              <div class="character__job__level">25</div>
+              <div class="character__job__name">Resistance Rank</div>
+              <div class="character__job__exp">-- / --</div>
+            </div>
+            <div class="character__job__list">
+              <div class="character__job__level">1</div>
+              <div class="character__job__name">Some Future Content</div>
+              <div class="character__job__exp">0 / 100</div>
+            </div>
+            "#,
+        );
+        let content = parse(html.root_element(), Language::English.locale())?;
+        assert_eq!(
+            content.0.into_values().collect::<Vec<ContentSnapshot>>(),
+            vec![
+                ContentSnapshot {
+                    content: Content::Eureka,
+                    level: Some(35),
+                    exp: Some((12345, 67890)),
+                },
+                ContentSnapshot {
+                    content: Content::Bozja,
+                    level: Some(25),
+                    exp: None,
+                },
+            ]
+        );
+        Ok(())
+    }
+}
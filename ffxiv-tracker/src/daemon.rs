@@ -0,0 +1,175 @@
+use std::sync::mpsc;
+use std::time::Duration;
+
+use crate::config::{Character, Config, Interval};
+use crate::db::{SnapshotOutcome, TrackerDatabase};
+use crate::error::Error;
+use crate::profile::Profile;
+use crate::region::Region;
+use chrono::{DateTime, TimeDelta, Utc};
+use tracing::{event, info_span, Level};
+
+/// First delay after a failed snapshot. Doubles with each consecutive failure.
+const BACKOFF_BASE: TimeDelta = TimeDelta::minutes(1);
+/// Longest delay after repeated failures.
+const BACKOFF_MAX: TimeDelta = TimeDelta::hours(6);
+
+/// When a character is next due to be polled.
+#[derive(Debug)]
+struct Schedule<'a> {
+    character: &'a Character,
+    interval: TimeDelta,
+    next: DateTime<Utc>,
+    /// Consecutive failed snapshots.
+    failures: u32,
+}
+
+/// Poll every character in the config on its own interval until SIGINT or
+/// SIGTERM is received.
+///
+/// Signals are only handled between snapshots, so a snapshot in progress is
+/// always finished and written before shutting down.
+pub fn run(config: &Config, default_interval: TimeDelta) -> Result<(), Error> {
+    let (shutdown, signal) = mpsc::channel();
+    ctrlc::set_handler(move || {
+        let _ = shutdown.send(());
+    })
+    .map_err(Error::Signal)?;
+
+    let mut database = TrackerDatabase::open(&config.database)?;
+    let now = Utc::now();
+    let mut schedules: Vec<Schedule> = vec![];
+    for character in &config.characters {
+        let interval = match character.interval {
+            Some(Interval(interval)) => interval,
+            None => default_interval,
+        };
+        // resume where we left off, rather than polling everyone on startup
+        let next = match database.last_observed(character.id)? {
+            Some(last_observed) => (last_observed + jitter(interval)).max(now),
+            None => now,
+        };
+        schedules.push(Schedule {
+            character,
+            interval,
+            next,
+            failures: 0,
+        });
+    }
+    event!(
+        Level::INFO,
+        "tracking {} characters in {}",
+        schedules.len(),
+        config.database.display()
+    );
+
+    loop {
+        let Some(index) = (0..schedules.len()).min_by_key(|&index| schedules[index].next) else {
+            event!(Level::WARN, "no characters to track");
+            return Ok(());
+        };
+        let schedule = &mut schedules[index];
+        let wait = (schedule.next - Utc::now())
+            .to_std()
+            .unwrap_or(Duration::ZERO);
+        match signal.recv_timeout(wait) {
+            Ok(()) | Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+        }
+
+        let character = schedule.character;
+        let span = info_span!(
+            "character",
+            id = character.id,
+            nickname = character.nickname,
+            region = %character.region
+        );
+        let _guard = span.enter();
+        match poll(&mut database, character.id, character.region) {
+            Ok(()) => {
+                schedule.failures = 0;
+                schedule.next = Utc::now() + jitter(schedule.interval);
+            }
+            Err(e) if e.is_not_found() => {
+                event!(Level::WARN, "character not found, no longer tracking: {e}");
+                schedules.remove(index);
+            }
+            Err(e) if !e.is_retryable() => {
+                // most likely a change to lodestone's layout, which won't fix
+                // itself, so check back at the usual interval
+                event!(Level::ERROR, "snapshot failed, needs attention: {e}");
+                schedule.next = Utc::now() + jitter(schedule.interval);
+            }
+            Err(e) => {
+                schedule.failures += 1;
+                let delay = backoff(schedule.failures);
+                event!(
+                    Level::ERROR,
+                    "snapshot failed ({} in a row), retrying in {} minutes: {e}",
+                    schedule.failures,
+                    delay.num_minutes()
+                );
+                schedule.next = Utc::now() + delay;
+            }
+        }
+    }
+
+    event!(Level::INFO, "shutting down");
+    Ok(())
+}
+
+fn poll(database: &mut TrackerDatabase, user_id: u64, region: Region) -> Result<(), Error> {
+    let profile = Profile::get(user_id, region)?;
+    match database.snapshot(profile)? {
+        SnapshotOutcome::Created(snapshot_id) => {
+            event!(Level::INFO, "created snapshot {snapshot_id}")
+        }
+        SnapshotOutcome::Unchanged(snapshot_id) => {
+            event!(Level::INFO, "unchanged since snapshot {snapshot_id}")
+        }
+    }
+    Ok(())
+}
+
+/// Randomize an interval by up to 10% either way, so that characters added at
+/// the same time don't stay in lockstep.
+fn jitter(interval: TimeDelta) -> TimeDelta {
+    let spread = interval.num_milliseconds() / 10;
+    if spread <= 0 {
+        return interval;
+    }
+    interval + TimeDelta::milliseconds(fastrand::i64(-spread..=spread))
+}
+
+/// Delay before retrying after `failures` consecutive failures.
+fn backoff(failures: u32) -> TimeDelta {
+    let factor = 2_i32.saturating_pow(failures.saturating_sub(1));
+    BACKOFF_BASE
+        .checked_mul(factor)
+        .unwrap_or(BACKOFF_MAX)
+        .min(BACKOFF_MAX)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn exponential_backoff() {
+        assert_eq!(backoff(1), TimeDelta::minutes(1));
+        assert_eq!(backoff(2), TimeDelta::minutes(2));
+        assert_eq!(backoff(4), TimeDelta::minutes(8));
+        assert_eq!(backoff(20), BACKOFF_MAX);
+        assert_eq!(backoff(u32::MAX), BACKOFF_MAX);
+    }
+
+    #[test]
+    fn jitter_within_ten_percent() {
+        let interval = TimeDelta::hours(1);
+        for _ in 0..100 {
+            let jittered = jitter(interval);
+            assert!(jittered >= TimeDelta::minutes(54));
+            assert!(jittered <= TimeDelta::minutes(66));
+        }
+    }
+}
@@ -1,75 +1,1537 @@
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str::FromStr;
 
+use crate::attributes::{Attribute, Attributes};
+use crate::collection::{Achievement, Collectable, Collection};
+use crate::content::{Content, ContentSnapshot};
+use crate::error::Error;
+use crate::free_company::{FreeCompany, FreeCompanyMember};
+use crate::gear::{Equipment, GearSlot};
+use crate::job::{Job, JobSnapshot};
 use crate::profile::Profile;
+use chrono::{DateTime, Utc};
 use rusqlite::{named_params, Connection};
+use strum::IntoEnumIterator;
 
+/// Schema migrations, applied in order. The schema version of a database is
+/// stored in `PRAGMA user_version`, and equals the number of migrations which
+/// have been applied to it.
+///
+/// Migrations are forward-only: never edit a migration once it has been
+/// released, add a new one instead.
+const MIGRATIONS: &[&str] = &[
+    include_str!("migrations/0001_initial.sql"),
+    include_str!("migrations/0002_captured_at.sql"),
+    include_str!("migrations/0003_snapshot_observations.sql"),
+    include_str!("migrations/0004_free_companies.sql"),
+    include_str!("migrations/0005_equipment.sql"),
+    include_str!("migrations/0006_attributes.sql"),
+    include_str!("migrations/0007_active_job.sql"),
+    include_str!("migrations/0008_content.sql"),
+    include_str!("migrations/0009_collections.sql"),
+];
+
+/// A tracker database, holding a connection open for its whole lifetime.
 #[derive(Debug)]
 pub struct TrackerDatabase {
-    pub path: PathBuf,
+    connection: Connection,
+}
+
+/// A stored profile snapshot, without any of the profile details.
+#[derive(Debug)]
+pub struct SnapshotRecord {
+    pub snapshot_id: i64,
+    /// `None` for snapshots recorded before capture times were tracked.
+    pub captured_at: Option<DateTime<Utc>>,
+}
+
+/// A player with stored snapshots, as of their latest snapshot.
+#[derive(Debug, PartialEq)]
+pub struct CharacterRecord {
+    pub user_id: u64,
+    pub name: String,
+    pub server: String,
+    pub snapshot_id: i64,
+    /// `None` if the latest snapshot was recorded before capture times were
+    /// tracked.
+    pub captured_at: Option<DateTime<Utc>>,
+}
+
+/// Experience of a job or special content, as recorded in a stored snapshot.
+#[derive(Debug, PartialEq)]
+pub struct ExperienceRecord {
+    /// `None` for snapshots recorded before capture times were tracked.
+    pub captured_at: Option<DateTime<Utc>>,
+    pub level: Option<u64>,
+    pub exp: Option<(u64, u64)>,
+}
+
+/// Profile details stored alongside each snapshot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileFields {
+    pub free_company: Option<String>,
+    pub free_company_id: Option<u64>,
+    pub name: String,
+    pub nameday: String,
+    pub guardian: String,
+    pub city_state: String,
+    pub server: String,
+    pub race: String,
+    pub clan: String,
+    pub gender: String,
+    pub grand_company: Option<String>,
+    pub grand_company_rank: Option<String>,
+    pub hp: u64,
+    pub mp: u64,
+    pub active_job: Option<Job>,
+    pub active_level: Option<u64>,
+}
+
+impl From<&Profile> for ProfileFields {
+    fn from(profile: &Profile) -> Self {
+        ProfileFields {
+            free_company: profile.free_company.clone(),
+            free_company_id: profile.free_company_id,
+            name: profile.name.clone(),
+            nameday: profile.nameday.clone(),
+            guardian: profile.guardian.clone(),
+            city_state: profile.city_state.clone(),
+            server: profile.server.clone(),
+            race: profile.race.clone(),
+            clan: profile.clan.clone(),
+            gender: profile.gender.clone(),
+            grand_company: profile.grand_company.clone(),
+            grand_company_rank: profile.grand_company_rank.clone(),
+            hp: profile.hp,
+            mp: profile.mp,
+            active_job: profile.active_job,
+            active_level: profile.active_level,
+        }
+    }
+}
+
+/// A profile snapshot loaded back from the database.
+///
+/// Jobs are kept as a plain map rather than a [`crate::job::PlayerJobSnapshot`],
+/// since snapshots taken before a job was released won't include it.
+#[derive(Debug)]
+pub struct StoredSnapshot {
+    pub snapshot_id: i64,
+    pub user_id: u64,
+    /// `None` for snapshots recorded before capture times were tracked.
+    pub captured_at: Option<DateTime<Utc>>,
+    pub fields: ProfileFields,
+    /// Empty for snapshots recorded before attributes were tracked.
+    pub attributes: Attributes,
+    /// Empty for snapshots recorded before gear was tracked.
+    pub equipment: Vec<Equipment>,
+    pub jobs: BTreeMap<Job, JobSnapshot>,
+    /// Empty for snapshots recorded before special content was tracked.
+    pub content: BTreeMap<Content, ContentSnapshot>,
+}
+
+/// A player's attributes, as recorded in a stored snapshot.
+#[derive(Debug)]
+pub struct AttributeRecord {
+    /// `None` for snapshots recorded before capture times were tracked.
+    pub captured_at: Option<DateTime<Utc>>,
+    pub attributes: Attributes,
+}
+
+/// A player's equipped gear, as recorded in a stored snapshot.
+#[derive(Debug)]
+pub struct EquipmentRecord {
+    /// `None` for snapshots recorded before capture times were tracked.
+    pub captured_at: Option<DateTime<Utc>>,
+    pub equipment: Vec<Equipment>,
+}
+
+/// A free company roster loaded back from the database.
+#[derive(Debug)]
+pub struct StoredRoster {
+    pub roster_id: i64,
+    pub captured_at: DateTime<Utc>,
+    pub members: Vec<FreeCompanyMember>,
+}
+
+/// Totals from a stored collection snapshot.
+#[derive(Debug, PartialEq)]
+pub struct CollectionRecord {
+    pub collection_id: i64,
+    pub captured_at: DateTime<Utc>,
+    /// `None` while the character's achievements were private.
+    pub achievement_points: Option<u64>,
+    pub achievements: u64,
+    pub mounts: u64,
+    pub minions: u64,
+}
+
+/// An achievement loaded back from the database, with when it was first seen.
+#[derive(Debug)]
+pub struct StoredAchievement {
+    pub achievement: Achievement,
+    pub first_seen: DateTime<Utc>,
+}
+
+/// A mount or minion loaded back from the database, with when it was first
+/// seen.
+#[derive(Debug)]
+pub struct StoredCollectable {
+    pub name: String,
+    pub first_seen: DateTime<Utc>,
+}
+
+/// Achievements and collectables recorded for the first time by a collection
+/// snapshot.
+#[derive(Debug)]
+pub struct CollectionUpdate {
+    pub collection_id: i64,
+    pub achievements: Vec<Achievement>,
+    pub collectables: Vec<(Collectable, String)>,
+}
+
+/// The result of recording a profile.
+#[derive(Debug, PartialEq)]
+pub enum SnapshotOutcome {
+    /// The profile changed, and was stored as a new snapshot.
+    Created(i64),
+    /// The profile was identical to this existing snapshot, so only the time
+    /// it was observed was recorded.
+    Unchanged(i64),
 }
 
 impl TrackerDatabase {
-    pub fn init(&self) -> Result<(), String> {
-        let db: Connection =
-            Connection::open(&self.path).map_err(|e| format!("failed to open path: {}", e))?;
-        db.execute_batch(include_str!("init.sql"))
-            .map_err(|e| format!("failed to run init script: {}", e))
+    /// Open the database, and upgrade it to the latest schema version.
+    pub fn open(path: &Path) -> Result<TrackerDatabase, Error> {
+        let mut connection: Connection =
+            Connection::open(path).map_err(Error::database("failed to open path"))?;
+        migrate(&mut connection)?;
+        Ok(TrackerDatabase { connection })
     }
 
-    pub fn snapshot(&self, profile: Profile) -> Result<(), String> {
-        let db: Connection =
-            Connection::open(&self.path).map_err(|e| format!("failed to open path: {}", e))?;
-        db.execute(
-            "INSERT OR IGNORE INTO profile_snapshots (
-                user_id, free_company, name, nameday, guardian, city_state, server,
-                race, clan, gender, grand_company, grand_company_rank, hp, mp
+    /// Record a profile, unless it is unchanged since the latest snapshot.
+    ///
+    /// The whole snapshot is written in a single transaction, so a failure
+    /// never leaves a profile with a partial set of jobs.
+    pub fn snapshot(&mut self, profile: Profile) -> Result<SnapshotOutcome, Error> {
+        let tx = self
+            .connection
+            .transaction()
+            .map_err(Error::database("failed to start transaction"))?;
+        let outcome = write_snapshot(&tx, profile)?;
+        tx.commit()
+            .map_err(Error::database("failed to commit snapshot"))?;
+        Ok(outcome)
+    }
+
+    /// List snapshots of a player, oldest first.
+    ///
+    /// Snapshots with an unknown capture time sort before all others, and are
+    /// excluded whenever `since` or `until` is given.
+    pub fn snapshots(
+        &self,
+        user_id: u64,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<SnapshotRecord>, Error> {
+        query_snapshots(&self.connection, user_id, since, until)
+    }
+
+    /// Every player with a stored snapshot, by name.
+    pub fn characters(&self) -> Result<Vec<CharacterRecord>, Error> {
+        let mut statement = self
+            .connection
+            .prepare(
+                "SELECT p.user_id, p.name, p.server, p.snapshot_id, p.captured_at
+                FROM profile_snapshots p
+                WHERE p.snapshot_id = (
+                    SELECT q.snapshot_id FROM profile_snapshots q
+                    WHERE q.user_id = p.user_id
+                    ORDER BY q.captured_at DESC, q.snapshot_id DESC
+                    LIMIT 1
+                )
+                ORDER BY p.name, p.user_id",
+            )
+            .map_err(Error::database("failed to prepare query"))?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok(CharacterRecord {
+                    user_id: row.get("user_id")?,
+                    name: row.get("name")?,
+                    server: row.get("server")?,
+                    snapshot_id: row.get("snapshot_id")?,
+                    captured_at: row.get("captured_at")?,
+                })
+            })
+            .map_err(Error::database("failed to query characters"))?;
+        rows.collect::<Result<Vec<CharacterRecord>, _>>()
+            .map_err(Error::database("failed to read character"))
+    }
+
+    /// Experience of a player over time for each job (or only `job`), oldest
+    /// first.
+    ///
+    /// Filtering by time follows the same rules as [`TrackerDatabase::snapshots`].
+    pub fn history(
+        &self,
+        user_id: u64,
+        job: Option<Job>,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<BTreeMap<Job, Vec<ExperienceRecord>>, Error> {
+        let mut statement = self
+            .connection
+            .prepare(
+                "SELECT p.captured_at, e.class_name, e.xp_level, e.current_xp, e.max_xp
+                FROM experience_snapshots e
+                JOIN profile_snapshots p ON p.snapshot_id = e.snapshot_id
+                WHERE p.user_id = :user_id
+                    AND (:class_name IS NULL OR e.class_name = :class_name)
+                    AND (:since IS NULL OR p.captured_at >= :since)
+                    AND (:until IS NULL OR p.captured_at <= :until)
+                ORDER BY p.captured_at, p.snapshot_id",
+            )
+            .map_err(Error::database("failed to prepare query"))?;
+        let rows = statement
+            .query_map(
+                named_params! {
+                    ":user_id": user_id,
+                    ":class_name": job.map(|job| job.to_string()),
+                    ":since": since,
+                    ":until": until,
+                },
+                |row| {
+                    Ok((
+                        row.get::<_, String>("class_name")?,
+                        row.get::<_, Option<u64>>("current_xp")?,
+                        row.get::<_, Option<u64>>("max_xp")?,
+                        ExperienceRecord {
+                            captured_at: row.get("captured_at")?,
+                            level: row.get("xp_level")?,
+                            exp: None,
+                        },
+                    ))
+                },
+            )
+            .map_err(Error::database("failed to query experience"))?;
+
+        let mut history: BTreeMap<Job, Vec<ExperienceRecord>> = BTreeMap::new();
+        for row in rows {
+            let (class_name, current_xp, max_xp, mut record) =
+                row.map_err(Error::database("failed to read experience"))?;
+            let job = Job::from_str(&class_name).map_err(|_| Error::UnknownJob(class_name))?;
+            record.exp = current_xp.zip(max_xp);
+            history.entry(job).or_default().push(record);
+        }
+        Ok(history)
+    }
+
+    /// Special content progression of a player over time, oldest first.
+    ///
+    /// Filtering by time follows the same rules as [`TrackerDatabase::snapshots`].
+    pub fn content_history(
+        &self,
+        user_id: u64,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<BTreeMap<Content, Vec<ExperienceRecord>>, Error> {
+        let mut statement = self
+            .connection
+            .prepare(
+                "SELECT p.captured_at, c.content, c.level, c.current_exp, c.max_exp
+                FROM content_snapshots c
+                JOIN profile_snapshots p ON p.snapshot_id = c.snapshot_id
+                WHERE p.user_id = :user_id
+                    AND (:since IS NULL OR p.captured_at >= :since)
+                    AND (:until IS NULL OR p.captured_at <= :until)
+                ORDER BY p.captured_at, p.snapshot_id",
+            )
+            .map_err(Error::database("failed to prepare query"))?;
+        let rows = statement
+            .query_map(
+                named_params! {
+                    ":user_id": user_id,
+                    ":since": since,
+                    ":until": until,
+                },
+                |row| {
+                    Ok((
+                        row.get::<_, String>("content")?,
+                        row.get::<_, Option<u64>>("current_exp")?,
+                        row.get::<_, Option<u64>>("max_exp")?,
+                        ExperienceRecord {
+                            captured_at: row.get("captured_at")?,
+                            level: row.get("level")?,
+                            exp: None,
+                        },
+                    ))
+                },
+            )
+            .map_err(Error::database("failed to query content"))?;
+
+        let mut history: BTreeMap<Content, Vec<ExperienceRecord>> = BTreeMap::new();
+        for row in rows {
+            let (name, current_exp, max_exp, mut record) =
+                row.map_err(Error::database("failed to read content"))?;
+            let content = Content::from_str(&name).map_err(|_| Error::InvalidRecord {
+                field: "content",
+                value: name,
+            })?;
+            record.exp = current_exp.zip(max_exp);
+            history.entry(content).or_default().push(record);
+        }
+        Ok(history)
+    }
+
+    /// When a player was last seen, whether as a new snapshot or unchanged.
+    pub fn last_observed(&self, user_id: u64) -> Result<Option<DateTime<Utc>>, Error> {
+        self.connection
+            .query_row(
+                "SELECT MAX(observed_at) FROM (
+                    SELECT captured_at AS observed_at FROM profile_snapshots
+                    WHERE user_id = :user_id
+                    UNION ALL
+                    SELECT o.observed_at FROM snapshot_observations o
+                    JOIN profile_snapshots p ON p.snapshot_id = o.snapshot_id
+                    WHERE p.user_id = :user_id
+                )",
+                named_params! { ":user_id": user_id },
+                |row| row.get(0),
+            )
+            .map_err(Error::database("failed to query last observation"))
+    }
+
+    /// Write a compacted copy of the database to `path`, which must not
+    /// already exist.
+    ///
+    /// The copy is consistent even while snapshots are being recorded.
+    pub fn export(&self, path: &Path) -> Result<(), Error> {
+        self.connection
+            .execute(
+                "VACUUM INTO :path",
+                named_params! { ":path": path.to_string_lossy() },
+            )
+            .map_err(Error::database("failed to export database"))?;
+        Ok(())
+    }
+
+    /// Load a stored snapshot, including its jobs.
+    pub fn load_snapshot(&self, snapshot_id: i64) -> Result<StoredSnapshot, Error> {
+        load_snapshot(&self.connection, snapshot_id)
+    }
+
+    /// Attributes of a player over time, oldest first. Snapshots without any
+    /// attributes recorded are skipped.
+    pub fn attribute_history(&self, user_id: u64) -> Result<Vec<AttributeRecord>, Error> {
+        let mut history: Vec<AttributeRecord> = vec![];
+        for snapshot in query_snapshots(&self.connection, user_id, None, None)? {
+            let attributes = load_attributes(&self.connection, snapshot.snapshot_id)?;
+            if attributes != Attributes::default() {
+                history.push(AttributeRecord {
+                    captured_at: snapshot.captured_at,
+                    attributes,
+                });
+            }
+        }
+        Ok(history)
+    }
+
+    /// Equipped gear of a player over time, oldest first. Snapshots without
+    /// any gear recorded are skipped.
+    pub fn equipment_history(&self, user_id: u64) -> Result<Vec<EquipmentRecord>, Error> {
+        let mut history: Vec<EquipmentRecord> = vec![];
+        for snapshot in query_snapshots(&self.connection, user_id, None, None)? {
+            let equipment = load_equipment(&self.connection, snapshot.snapshot_id)?;
+            if !equipment.is_empty() {
+                history.push(EquipmentRecord {
+                    captured_at: snapshot.captured_at,
+                    equipment,
+                });
+            }
+        }
+        Ok(history)
+    }
+
+    /// Record a free company and its current roster.
+    pub fn snapshot_free_company(&mut self, free_company: &FreeCompany) -> Result<i64, Error> {
+        let tx = self
+            .connection
+            .transaction()
+            .map_err(Error::database("failed to start transaction"))?;
+        tx.execute(
+            "INSERT INTO free_company_snapshots (
+                free_company_id, captured_at, name, tag, world, grand_company, slogan
             ) VALUES (
-                :user_id, :free_company, :name, :nameday, :guardian, :city_state, :server,
-                :race, :clan, :gender, :grand_company, :grand_company_rank, :hp, :mp
+                :free_company_id, :captured_at, :name, :tag, :world, :grand_company, :slogan
             )",
             named_params! {
-                ":user_id": profile.user_id,
-                ":free_company": profile.free_company,
-                ":name": profile.name,
-                ":nameday": profile.nameday,
-                ":guardian": profile.guardian,
-                ":city_state": profile.city_state,
-                ":server": profile.server,
-                ":clan": profile.clan,
-                ":race": profile.race,
-                ":gender": profile.gender,
-                ":grand_company": profile.grand_company,
-                ":grand_company_rank": profile.grand_company_rank,
-                ":hp": profile.hp,
-                ":mp": profile.mp
+                ":free_company_id": free_company.free_company_id.to_string(),
+                ":captured_at": free_company.captured_at,
+                ":name": free_company.name,
+                ":tag": free_company.tag,
+                ":world": free_company.world,
+                ":grand_company": free_company.grand_company,
+                ":slogan": free_company.slogan,
             },
         )
-        .map_err(|e| format!("failed to insert: {}", e))?;
-        let snapshot_id = db.last_insert_rowid();
-        println!("created snapshot ID: {}", snapshot_id);
-
-        for (job, job_snapshot) in profile.jobs.0 {
-            let (current_xp, max_xp) = match job_snapshot.exp {
-                Some((current_xp, max_xp)) => (Some(current_xp), Some(max_xp)),
-                _ => (None, None),
-            };
+        .map_err(Error::database("failed to insert free company"))?;
+        let roster_id = tx.last_insert_rowid();
+
+        {
+            let mut insert_member = tx
+                .prepare(
+                    "INSERT OR IGNORE INTO free_company_members (roster_id, user_id, name, rank)
+                    VALUES (:roster_id, :user_id, :name, :rank)",
+                )
+                .map_err(Error::database("failed to prepare insert"))?;
+            for member in &free_company.members {
+                insert_member
+                    .execute(named_params! {
+                        ":roster_id": roster_id,
+                        ":user_id": member.user_id,
+                        ":name": member.name,
+                        ":rank": member.rank,
+                    })
+                    .map_err(Error::database("failed to insert member"))?;
+            }
+        }
+        tx.commit()
+            .map_err(Error::database("failed to commit free company"))?;
+        Ok(roster_id)
+    }
+
+    /// Every stored roster of a free company, oldest first.
+    pub fn rosters(&self, free_company_id: u64) -> Result<Vec<StoredRoster>, Error> {
+        let mut statement = self
+            .connection
+            .prepare(
+                "SELECT s.roster_id, s.captured_at, m.user_id, m.name, m.rank
+                FROM free_company_snapshots s
+                LEFT JOIN free_company_members m ON m.roster_id = s.roster_id
+                WHERE s.free_company_id = :free_company_id
+                ORDER BY s.captured_at, s.roster_id, m.name",
+            )
+            .map_err(Error::database("failed to prepare query"))?;
+        let rows = statement
+            .query_map(
+                named_params! { ":free_company_id": free_company_id.to_string() },
+                |row| {
+                    let member = match row.get::<_, Option<u64>>("user_id")? {
+                        Some(user_id) => Some(FreeCompanyMember {
+                            user_id,
+                            name: row.get("name")?,
+                            rank: row.get("rank")?,
+                        }),
+                        None => None,
+                    };
+                    Ok((row.get("roster_id")?, row.get("captured_at")?, member))
+                },
+            )
+            .map_err(Error::database("failed to query rosters"))?;
+
+        let mut rosters: Vec<StoredRoster> = vec![];
+        for row in rows {
+            let (roster_id, captured_at, member) =
+                row.map_err(Error::database("failed to read roster"))?;
+            if rosters.last().map(|roster| roster.roster_id) != Some(roster_id) {
+                rosters.push(StoredRoster {
+                    roster_id,
+                    captured_at,
+                    members: vec![],
+                });
+            }
+            if let (Some(roster), Some(member)) = (rosters.last_mut(), member) {
+                roster.members.push(member);
+            }
+        }
+        Ok(rosters)
+    }
+
+    /// Record a character's collection. Achievements and collectables seen
+    /// before keep the snapshot they were first seen in.
+    pub fn snapshot_collection(
+        &mut self,
+        collection: &Collection,
+    ) -> Result<CollectionUpdate, Error> {
+        let tx = self
+            .connection
+            .transaction()
+            .map_err(Error::database("failed to start transaction"))?;
+        tx.execute(
+            "INSERT INTO collection_snapshots (
+                user_id, captured_at, achievement_points, achievements, mounts, minions
+            ) VALUES (
+                :user_id, :captured_at, :achievement_points, :achievements, :mounts, :minions
+            )",
+            named_params! {
+                ":user_id": collection.user_id,
+                ":captured_at": collection.captured_at,
+                ":achievement_points": collection.achievement_points,
+                ":achievements": collection.achievements.len(),
+                ":mounts": collection.mounts.len(),
+                ":minions": collection.minions.len(),
+            },
+        )
+        .map_err(Error::database("failed to insert collection"))?;
+        let collection_id = tx.last_insert_rowid();
+
+        let mut update = CollectionUpdate {
+            collection_id,
+            achievements: vec![],
+            collectables: vec![],
+        };
+        {
+            let mut insert_achievement = tx
+                .prepare(
+                    "INSERT OR IGNORE INTO achievements (
+                        user_id, achievement_id, name, points, earned_at, first_seen
+                    ) VALUES (
+                        :user_id, :achievement_id, :name, :points, :earned_at, :first_seen
+                    )",
+                )
+                .map_err(Error::database("failed to prepare insert"))?;
+            for achievement in &collection.achievements {
+                let inserted = insert_achievement
+                    .execute(named_params! {
+                        ":user_id": collection.user_id,
+                        ":achievement_id": achievement.achievement_id,
+                        ":name": achievement.name,
+                        ":points": achievement.points,
+                        ":earned_at": achievement.earned_at,
+                        ":first_seen": collection_id,
+                    })
+                    .map_err(Error::database("failed to insert achievement"))?;
+                if inserted > 0 {
+                    update.achievements.push(achievement.clone());
+                }
+            }
 
+            let mut insert_collectable = tx
+                .prepare(
+                    "INSERT OR IGNORE INTO collectables (user_id, kind, name, first_seen)
+                    VALUES (:user_id, :kind, :name, :first_seen)",
+                )
+                .map_err(Error::database("failed to prepare insert"))?;
+            for kind in [Collectable::Mount, Collectable::Minion] {
+                for name in collection.collectables(kind) {
+                    let inserted = insert_collectable
+                        .execute(named_params! {
+                            ":user_id": collection.user_id,
+                            ":kind": kind.to_string(),
+                            ":name": name,
+                            ":first_seen": collection_id,
+                        })
+                        .map_err(Error::database("failed to insert collectable"))?;
+                    if inserted > 0 {
+                        update.collectables.push((kind, name.clone()));
+                    }
+                }
+            }
+        }
+        tx.commit()
+            .map_err(Error::database("failed to commit collection"))?;
+        Ok(update)
+    }
+
+    /// Collection snapshots of a character, oldest first, for charting
+    /// achievement points and collection sizes over time.
+    pub fn collection_history(&self, user_id: u64) -> Result<Vec<CollectionRecord>, Error> {
+        let mut statement = self
+            .connection
+            .prepare(
+                "SELECT collection_id, captured_at, achievement_points, achievements, mounts, minions
+                FROM collection_snapshots
+                WHERE user_id = :user_id
+                ORDER BY captured_at, collection_id",
+            )
+            .map_err(Error::database("failed to prepare query"))?;
+        let rows = statement
+            .query_map(named_params! { ":user_id": user_id }, |row| {
+                Ok(CollectionRecord {
+                    collection_id: row.get("collection_id")?,
+                    captured_at: row.get("captured_at")?,
+                    achievement_points: row.get("achievement_points")?,
+                    achievements: row.get("achievements")?,
+                    mounts: row.get("mounts")?,
+                    minions: row.get("minions")?,
+                })
+            })
+            .map_err(Error::database("failed to query collections"))?;
+        rows.collect::<Result<Vec<CollectionRecord>, _>>()
+            .map_err(Error::database("failed to read collection"))
+    }
+
+    /// Achievements of a character, in the order they were earned. Those
+    /// without a date sort by when they were first seen.
+    pub fn achievements(&self, user_id: u64) -> Result<Vec<StoredAchievement>, Error> {
+        let mut statement = self
+            .connection
+            .prepare(
+                "SELECT a.achievement_id, a.name, a.points, a.earned_at,
+                    c.captured_at AS first_seen
+                FROM achievements a
+                JOIN collection_snapshots c ON c.collection_id = a.first_seen
+                WHERE a.user_id = :user_id
+                ORDER BY COALESCE(a.earned_at, c.captured_at), a.achievement_id",
+            )
+            .map_err(Error::database("failed to prepare query"))?;
+        let rows = statement
+            .query_map(named_params! { ":user_id": user_id }, |row| {
+                Ok(StoredAchievement {
+                    achievement: Achievement {
+                        achievement_id: row.get("achievement_id")?,
+                        name: row.get("name")?,
+                        points: row.get("points")?,
+                        earned_at: row.get("earned_at")?,
+                    },
+                    first_seen: row.get("first_seen")?,
+                })
+            })
+            .map_err(Error::database("failed to query achievements"))?;
+        rows.collect::<Result<Vec<StoredAchievement>, _>>()
+            .map_err(Error::database("failed to read achievement"))
+    }
+
+    /// Mounts or minions of a character, in the order they were first seen.
+    pub fn collectables(
+        &self,
+        user_id: u64,
+        kind: Collectable,
+    ) -> Result<Vec<StoredCollectable>, Error> {
+        let mut statement = self
+            .connection
+            .prepare(
+                "SELECT o.name, c.captured_at AS first_seen
+                FROM collectables o
+                JOIN collection_snapshots c ON c.collection_id = o.first_seen
+                WHERE o.user_id = :user_id AND o.kind = :kind
+                ORDER BY c.captured_at, o.name",
+            )
+            .map_err(Error::database("failed to prepare query"))?;
+        let rows = statement
+            .query_map(
+                named_params! { ":user_id": user_id, ":kind": kind.to_string() },
+                |row| {
+                    Ok(StoredCollectable {
+                        name: row.get("name")?,
+                        first_seen: row.get("first_seen")?,
+                    })
+                },
+            )
+            .map_err(Error::database("failed to query collectables"))?;
+        rows.collect::<Result<Vec<StoredCollectable>, _>>()
+            .map_err(Error::database("failed to read collectable"))
+    }
+}
+
+fn write_snapshot(db: &Connection, profile: Profile) -> Result<SnapshotOutcome, Error> {
+    if let Some(latest) = query_snapshots(db, profile.user_id, None, None)?.last() {
+        if unchanged_since(db, latest.snapshot_id, &profile)? {
             db.execute(
-                "INSERT INTO experience_snapshots (
+                "INSERT INTO snapshot_observations (snapshot_id, observed_at)
+                VALUES (:snapshot_id, :observed_at)",
+                named_params! {
+                    ":snapshot_id": latest.snapshot_id,
+                    ":observed_at": profile.captured_at,
+                },
+            )
+            .map_err(Error::database("failed to insert observation"))?;
+            return Ok(SnapshotOutcome::Unchanged(latest.snapshot_id));
+        }
+    }
+
+    db.execute(
+        "INSERT INTO profile_snapshots (
+            user_id, captured_at, free_company, free_company_id, name, nameday, guardian,
+            city_state, server, race, clan, gender, grand_company, grand_company_rank, hp, mp,
+            active_job, active_level
+        ) VALUES (
+            :user_id, :captured_at, :free_company, :free_company_id, :name, :nameday, :guardian,
+            :city_state, :server, :race, :clan, :gender, :grand_company, :grand_company_rank,
+            :hp, :mp, :active_job, :active_level
+        )",
+        named_params! {
+            ":user_id": profile.user_id,
+            ":captured_at": profile.captured_at,
+            ":free_company": profile.free_company,
+            ":free_company_id": profile.free_company_id.map(|id| id.to_string()),
+            ":name": profile.name,
+            ":nameday": profile.nameday,
+            ":guardian": profile.guardian,
+            ":city_state": profile.city_state,
+            ":server": profile.server,
+            ":clan": profile.clan,
+            ":race": profile.race,
+            ":gender": profile.gender,
+            ":grand_company": profile.grand_company,
+            ":grand_company_rank": profile.grand_company_rank,
+            ":hp": profile.hp,
+            ":mp": profile.mp,
+            ":active_job": profile.active_job.map(|job| job.to_string()),
+            ":active_level": profile.active_level,
+        },
+    )
+    .map_err(Error::database("failed to insert"))?;
+    let snapshot_id = db.last_insert_rowid();
+
+    let mut insert_experience = db
+        .prepare(
+            "INSERT INTO experience_snapshots (
                 snapshot_id, class_name, xp_level, current_xp, max_xp
             ) VALUES (
                 :snapshot_id, :class_name, :xp_level, :current_xp, :max_xp
             )",
-                named_params! {
+        )
+        .map_err(Error::database("failed to prepare insert"))?;
+    for (job, job_snapshot) in profile.jobs.0 {
+        let (current_xp, max_xp) = match job_snapshot.exp {
+            Some((current_xp, max_xp)) => (Some(current_xp), Some(max_xp)),
+            _ => (None, None),
+        };
+
+        insert_experience
+            .execute(named_params! {
+                ":snapshot_id": snapshot_id,
+                ":class_name": job.to_string(),
+                ":xp_level": job_snapshot.level,
+                ":current_xp": current_xp,
+                ":max_xp": max_xp,
+            })
+            .map_err(Error::database("failed to insert experience"))?;
+    }
+
+    let mut insert_attribute = db
+        .prepare(
+            "INSERT INTO attribute_snapshots (snapshot_id, attribute, value)
+            VALUES (:snapshot_id, :attribute, :value)",
+        )
+        .map_err(Error::database("failed to prepare insert"))?;
+    for attribute in Attribute::iter() {
+        if let Some(value) = profile.attributes.get(attribute) {
+            insert_attribute
+                .execute(named_params! {
                     ":snapshot_id": snapshot_id,
-                    ":class_name": job.to_string(),
-                    ":xp_level": job_snapshot.level,
-                    ":current_xp": current_xp,
-                    ":max_xp": max_xp,
+                    ":attribute": attribute.to_string(),
+                    ":value": value,
+                })
+                .map_err(Error::database("failed to insert attribute"))?;
+        }
+    }
+
+    let mut insert_equipment = db
+        .prepare(
+            "INSERT INTO equipment_snapshots (
+                snapshot_id, slot, item_name, item_level, high_quality, materia, glamour
+            ) VALUES (
+                :snapshot_id, :slot, :item_name, :item_level, :high_quality, :materia, :glamour
+            )",
+        )
+        .map_err(Error::database("failed to prepare insert"))?;
+    for item in &profile.equipment {
+        insert_equipment
+            .execute(named_params! {
+                ":snapshot_id": snapshot_id,
+                ":slot": item.slot.to_string(),
+                ":item_name": item.name,
+                ":item_level": item.item_level,
+                ":high_quality": item.high_quality,
+                ":materia": serde_json::Value::from(item.materia.clone()).to_string(),
+                ":glamour": item.glamour,
+            })
+            .map_err(Error::database("failed to insert equipment"))?;
+    }
+
+    let mut insert_content = db
+        .prepare(
+            "INSERT INTO content_snapshots (
+                snapshot_id, content, level, current_exp, max_exp
+            ) VALUES (
+                :snapshot_id, :content, :level, :current_exp, :max_exp
+            )",
+        )
+        .map_err(Error::database("failed to prepare insert"))?;
+    for (content, content_snapshot) in &profile.content.0 {
+        insert_content
+            .execute(named_params! {
+                ":snapshot_id": snapshot_id,
+                ":content": content.to_string(),
+                ":level": content_snapshot.level,
+                ":current_exp": content_snapshot.exp.map(|(current, _)| current),
+                ":max_exp": content_snapshot.exp.map(|(_, max)| max),
+            })
+            .map_err(Error::database("failed to insert content"))?;
+    }
+    Ok(SnapshotOutcome::Created(snapshot_id))
+}
+
+/// Whether a profile is identical to a stored snapshot, ignoring capture time.
+fn unchanged_since(db: &Connection, snapshot_id: i64, profile: &Profile) -> Result<bool, Error> {
+    let stored = load_snapshot(db, snapshot_id)?;
+    Ok(stored.fields == ProfileFields::from(profile)
+        && stored.attributes == profile.attributes
+        && stored.equipment == profile.equipment
+        && stored.jobs == profile.jobs.0
+        && stored.content == profile.content.0)
+}
+
+fn load_snapshot(db: &Connection, snapshot_id: i64) -> Result<StoredSnapshot, Error> {
+    let (user_id, captured_at, mut fields, free_company_id, active_job) = db
+        .query_row(
+            "SELECT user_id, captured_at, free_company, free_company_id, name, nameday, guardian,
+                city_state, server, race, clan, gender, grand_company, grand_company_rank, hp, mp,
+                active_job, active_level
+            FROM profile_snapshots WHERE snapshot_id = :snapshot_id",
+            named_params! { ":snapshot_id": snapshot_id },
+            |row| {
+                let fields = ProfileFields {
+                    free_company: row.get("free_company")?,
+                    free_company_id: None,
+                    name: row.get("name")?,
+                    nameday: row.get("nameday")?,
+                    guardian: row.get("guardian")?,
+                    city_state: row.get("city_state")?,
+                    server: row.get("server")?,
+                    race: row.get("race")?,
+                    clan: row.get("clan")?,
+                    gender: row.get("gender")?,
+                    grand_company: row.get("grand_company")?,
+                    grand_company_rank: row.get("grand_company_rank")?,
+                    hp: row.get("hp")?,
+                    mp: row.get("mp")?,
+                    active_job: None,
+                    active_level: row.get("active_level")?,
+                };
+                Ok((
+                    row.get("user_id")?,
+                    row.get("captured_at")?,
+                    fields,
+                    row.get::<_, Option<String>>("free_company_id")?,
+                    row.get::<_, Option<String>>("active_job")?,
+                ))
+            },
+        )
+        .map_err(Error::database("failed to read snapshot"))?;
+    fields.free_company_id = free_company_id
+        .map(|id| {
+            id.parse().map_err(|_| Error::InvalidRecord {
+                field: "free_company_id",
+                value: id,
+            })
+        })
+        .transpose()?;
+    fields.active_job = active_job
+        .map(|job| Job::from_str(&job).map_err(|_| Error::UnknownJob(job)))
+        .transpose()?;
+
+    let mut statement = db
+        .prepare(
+            "SELECT class_name, xp_level, current_xp, max_xp FROM experience_snapshots
+            WHERE snapshot_id = :snapshot_id",
+        )
+        .map_err(Error::database("failed to prepare query"))?;
+    let rows = statement
+        .query_map(named_params! { ":snapshot_id": snapshot_id }, |row| {
+            Ok((
+                row.get::<_, String>("class_name")?,
+                row.get::<_, Option<u64>>("xp_level")?,
+                row.get::<_, Option<u64>>("current_xp")?,
+                row.get::<_, Option<u64>>("max_xp")?,
+            ))
+        })
+        .map_err(Error::database("failed to query experience"))?;
+    let mut jobs: BTreeMap<Job, JobSnapshot> = BTreeMap::new();
+    for row in rows {
+        let (class_name, level, current_xp, max_xp) =
+            row.map_err(Error::database("failed to read experience"))?;
+        let job = Job::from_str(&class_name).map_err(|_| Error::UnknownJob(class_name))?;
+        let exp = current_xp.zip(max_xp);
+        jobs.insert(job, JobSnapshot { job, level, exp });
+    }
+
+    Ok(StoredSnapshot {
+        snapshot_id,
+        user_id,
+        captured_at,
+        fields,
+        attributes: load_attributes(db, snapshot_id)?,
+        equipment: load_equipment(db, snapshot_id)?,
+        jobs,
+        content: load_content(db, snapshot_id)?,
+    })
+}
+
+fn load_attributes(db: &Connection, snapshot_id: i64) -> Result<Attributes, Error> {
+    let mut statement = db
+        .prepare(
+            "SELECT attribute, value FROM attribute_snapshots
+            WHERE snapshot_id = :snapshot_id",
+        )
+        .map_err(Error::database("failed to prepare query"))?;
+    let rows = statement
+        .query_map(named_params! { ":snapshot_id": snapshot_id }, |row| {
+            Ok((
+                row.get::<_, String>("attribute")?,
+                row.get::<_, u64>("value")?,
+            ))
+        })
+        .map_err(Error::database("failed to query attributes"))?;
+
+    let mut attributes = Attributes::default();
+    for row in rows {
+        let (name, value) = row.map_err(Error::database("failed to read attribute"))?;
+        let attribute = Attribute::from_str(&name).map_err(|_| Error::InvalidRecord {
+            field: "attribute",
+            value: name,
+        })?;
+        attributes.set(attribute, value);
+    }
+    Ok(attributes)
+}
+
+fn load_equipment(db: &Connection, snapshot_id: i64) -> Result<Vec<Equipment>, Error> {
+    let mut statement = db
+        .prepare(
+            "SELECT slot, item_name, item_level, high_quality, materia, glamour
+            FROM equipment_snapshots WHERE snapshot_id = :snapshot_id",
+        )
+        .map_err(Error::database("failed to prepare query"))?;
+    let rows = statement
+        .query_map(named_params! { ":snapshot_id": snapshot_id }, |row| {
+            Ok((
+                row.get::<_, String>("slot")?,
+                row.get::<_, String>("materia")?,
+                Equipment {
+                    slot: GearSlot::MainHand,
+                    name: row.get("item_name")?,
+                    item_level: row.get("item_level")?,
+                    high_quality: row.get("high_quality")?,
+                    materia: vec![],
+                    glamour: row.get("glamour")?,
                 },
+            ))
+        })
+        .map_err(Error::database("failed to query equipment"))?;
+
+    let mut equipment: Vec<Equipment> = vec![];
+    for row in rows {
+        let (slot, materia, mut item) = row.map_err(Error::database("failed to read equipment"))?;
+        item.slot = GearSlot::from_str(&slot).map_err(|_| Error::InvalidRecord {
+            field: "slot",
+            value: slot,
+        })?;
+        item.materia = serde_json::from_str(&materia).map_err(|_| Error::InvalidRecord {
+            field: "materia",
+            value: materia,
+        })?;
+        equipment.push(item);
+    }
+    equipment.sort_by_key(|item| item.slot);
+    Ok(equipment)
+}
+
+fn load_content(
+    db: &Connection,
+    snapshot_id: i64,
+) -> Result<BTreeMap<Content, ContentSnapshot>, Error> {
+    let mut statement = db
+        .prepare(
+            "SELECT content, level, current_exp, max_exp FROM content_snapshots
+            WHERE snapshot_id = :snapshot_id",
+        )
+        .map_err(Error::database("failed to prepare query"))?;
+    let rows = statement
+        .query_map(named_params! { ":snapshot_id": snapshot_id }, |row| {
+            Ok((
+                row.get::<_, String>("content")?,
+                row.get::<_, Option<u64>>("level")?,
+                row.get::<_, Option<u64>>("current_exp")?,
+                row.get::<_, Option<u64>>("max_exp")?,
+            ))
+        })
+        .map_err(Error::database("failed to query content"))?;
+
+    let mut snapshots: BTreeMap<Content, ContentSnapshot> = BTreeMap::new();
+    for row in rows {
+        let (name, level, current_exp, max_exp) =
+            row.map_err(Error::database("failed to read content"))?;
+        let content = Content::from_str(&name).map_err(|_| Error::InvalidRecord {
+            field: "content",
+            value: name,
+        })?;
+        let exp = current_exp.zip(max_exp);
+        snapshots.insert(
+            content,
+            ContentSnapshot {
+                content,
+                level,
+                exp,
+            },
+        );
+    }
+    Ok(snapshots)
+}
+
+fn query_snapshots(
+    db: &Connection,
+    user_id: u64,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+) -> Result<Vec<SnapshotRecord>, Error> {
+    let mut statement = db
+        .prepare(
+            "SELECT snapshot_id, captured_at FROM profile_snapshots
+            WHERE user_id = :user_id
+                AND (:since IS NULL OR captured_at >= :since)
+                AND (:until IS NULL OR captured_at <= :until)
+            ORDER BY captured_at, snapshot_id",
+        )
+        .map_err(Error::database("failed to prepare query"))?;
+    let rows = statement
+        .query_map(
+            named_params! {
+                ":user_id": user_id,
+                ":since": since,
+                ":until": until,
+            },
+            |row| {
+                Ok(SnapshotRecord {
+                    snapshot_id: row.get("snapshot_id")?,
+                    captured_at: row.get("captured_at")?,
+                })
+            },
+        )
+        .map_err(Error::database("failed to query snapshots"))?;
+    rows.collect::<Result<Vec<SnapshotRecord>, _>>()
+        .map_err(Error::database("failed to read snapshot"))
+}
+
+/// Apply all pending migrations to a database.
+///
+/// Refuses to touch databases with a schema version newer than this binary
+/// knows about, since they were written by a newer release.
+fn migrate(db: &mut Connection) -> Result<(), Error> {
+    let version: usize = db
+        .pragma_query_value(None, "user_version", |row| row.get(0))
+        .map_err(Error::database("failed to read schema version"))?;
+    if version > MIGRATIONS.len() {
+        Err(Error::SchemaTooNew {
+            version,
+            supported: MIGRATIONS.len(),
+        })?;
+    }
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        let tx = db
+            .transaction()
+            .map_err(Error::database("failed to start migration"))?;
+        tx.execute_batch(migration)
+            .map_err(Error::database("failed to run migration"))?;
+        tx.pragma_update(None, "user_version", index + 1)
+            .map_err(Error::database("failed to update schema version"))?;
+        tx.commit()
+            .map_err(Error::database("failed to commit migration"))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::content::PlayerContentSnapshot;
+    use crate::job::{Job, JobSnapshot, PlayerJobSnapshot};
+    use strum::IntoEnumIterator;
+
+    pub(crate) fn test_profile(captured_at: DateTime<Utc>) -> Profile {
+        let jobs = Job::iter()
+            .map(|job| JobSnapshot {
+                job,
+                level: Some(1),
+                exp: Some((0, 300)),
+            })
+            .collect::<Vec<JobSnapshot>>();
+        Profile {
+            user_id: 1,
+            captured_at,
+            free_company: None,
+            free_company_id: None,
+            name: "Test Player".to_string(),
+            nameday: "1st Sun of the 1st Astral Moon".to_string(),
+            guardian: "Halone, the Fury".to_string(),
+            city_state: "Ul'dah".to_string(),
+            server: "Adamantoise [Aether]".to_string(),
+            race: "Lalafell".to_string(),
+            clan: "Dunesfolk".to_string(),
+            gender: "♂".to_string(),
+            grand_company: None,
+            grand_company_rank: None,
+            hp: 100,
+            mp: 100,
+            active_job: Some(Job::Paladin),
+            active_level: Some(1),
+            attributes: Attributes::default(),
+            equipment: vec![],
+            jobs: PlayerJobSnapshot::try_from(jobs).unwrap(),
+            content: PlayerContentSnapshot::default(),
+        }
+    }
+
+    /// Midnight UTC, `d` days after the unix epoch.
+    pub(crate) fn day(d: i64) -> DateTime<Utc> {
+        DateTime::UNIX_EPOCH + chrono::Duration::days(d)
+    }
+
+    pub(crate) fn test_database() -> TrackerDatabase {
+        let mut connection = Connection::open_in_memory().unwrap();
+        migrate(&mut connection).unwrap();
+        TrackerDatabase { connection }
+    }
+
+    fn count(db: &TrackerDatabase, table: &str) -> i64 {
+        db.connection
+            .query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+                row.get(0)
+            })
+            .unwrap()
+    }
+
+    fn schema_version(db: &Connection) -> usize {
+        db.pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn migrate_fresh_database() -> Result<(), Error> {
+        let mut db = Connection::open_in_memory().unwrap();
+        migrate(&mut db)?;
+        assert_eq!(schema_version(&db), MIGRATIONS.len());
+
+        // migrating again is a no-op
+        migrate(&mut db)?;
+        assert_eq!(schema_version(&db), MIGRATIONS.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn migrate_unversioned_database() -> Result<(), Error> {
+        // databases created before migrations existed have the initial tables,
+        // but no schema version
+        let mut db = Connection::open_in_memory().unwrap();
+        db.execute_batch(MIGRATIONS[0]).unwrap();
+        assert_eq!(schema_version(&db), 0);
+
+        migrate(&mut db)?;
+        assert_eq!(schema_version(&db), MIGRATIONS.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn refuse_newer_database() {
+        let mut db = Connection::open_in_memory().unwrap();
+        db.pragma_update(None, "user_version", MIGRATIONS.len() + 1)
+            .unwrap();
+        assert!(migrate(&mut db).is_err());
+    }
+
+    #[test]
+    fn snapshots_ordered_by_capture_time() -> Result<(), Error> {
+        let mut db = Connection::open_in_memory().unwrap();
+        // a snapshot recorded before capture times were tracked
+        db.execute_batch(MIGRATIONS[0]).unwrap();
+        db.execute(
+            "INSERT INTO profile_snapshots (
+                user_id, name, nameday, guardian, city_state, server, race, clan, gender, hp, mp
+            ) VALUES (1, '', '', '', '', '', '', '', '', 0, 0)",
+            [],
+        )
+        .unwrap();
+        migrate(&mut db)?;
+
+        let insert = |captured_at: DateTime<Utc>| {
+            db.execute(
+                "INSERT INTO profile_snapshots (
+                    user_id, captured_at, name, nameday, guardian, city_state, server,
+                    race, clan, gender, hp, mp
+                ) VALUES (1, ?1, '', '', '', '', '', '', '', '', 0, 0)",
+                [captured_at],
             )
-            .map_err(|e| format!("failed to insert experience: {}", e))?;
+            .unwrap();
+        };
+        insert(day(3));
+        insert(day(1));
+        insert(day(2));
+
+        let all = query_snapshots(&db, 1, None, None)?;
+        let times: Vec<_> = all.iter().map(|s| s.captured_at).collect();
+        assert_eq!(times, vec![None, Some(day(1)), Some(day(2)), Some(day(3))]);
+
+        let bounded = query_snapshots(&db, 1, Some(day(2)), Some(day(3)))?;
+        let times: Vec<_> = bounded.iter().map(|s| s.captured_at).collect();
+        assert_eq!(times, vec![Some(day(2)), Some(day(3))]);
+
+        assert!(query_snapshots(&db, 2, None, None)?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn deduplicate_unchanged_snapshots() -> Result<(), Error> {
+        let mut db = test_database();
+
+        let first = db.snapshot(test_profile(day(1)))?;
+        let SnapshotOutcome::Created(snapshot_id) = first else {
+            panic!("expected new snapshot, got {first:?}");
+        };
+        assert_eq!(
+            db.snapshot(test_profile(day(2)))?,
+            SnapshotOutcome::Unchanged(snapshot_id)
+        );
+
+        let mut leveled = test_profile(day(3));
+        leveled.jobs.0.get_mut(&Job::Paladin).unwrap().exp = Some((150, 300));
+        assert!(matches!(
+            db.snapshot(leveled)?,
+            SnapshotOutcome::Created(id) if id != snapshot_id
+        ));
+
+        assert_eq!(count(&db, "snapshot_observations"), 1);
+        assert_eq!(db.last_observed(1)?, Some(day(3)));
+        assert_eq!(db.last_observed(2)?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn snapshot_is_atomic() {
+        let mut db = test_database();
+        db.connection
+            .execute_batch(
+                "CREATE TRIGGER fail_fisher BEFORE INSERT ON experience_snapshots
+                WHEN NEW.class_name = 'Fisher'
+                BEGIN SELECT RAISE(ABORT, 'no fishing'); END;",
+            )
+            .unwrap();
+
+        assert!(db.snapshot(test_profile(DateTime::UNIX_EPOCH)).is_err());
+        assert_eq!(count(&db, "profile_snapshots"), 0);
+        assert_eq!(count(&db, "experience_snapshots"), 0);
+    }
+
+    #[test]
+    fn history_by_job() -> Result<(), Error> {
+        let mut db = test_database();
+
+        db.snapshot(test_profile(day(1)))?;
+        let mut leveled = test_profile(day(2));
+        leveled.jobs.0.get_mut(&Job::DarkKnight).unwrap().level = Some(2);
+        db.snapshot(leveled)?;
+
+        let all = db.history(1, None, None, None)?;
+        assert_eq!(all.len(), Job::iter().count());
+        assert!(all.values().all(|records| records.len() == 2));
+
+        let dark_knight = db.history(1, Some(Job::DarkKnight), Some(day(2)), None)?;
+        assert_eq!(
+            dark_knight.get(&Job::DarkKnight),
+            Some(&vec![ExperienceRecord {
+                captured_at: Some(day(2)),
+                level: Some(2),
+                exp: Some((0, 300)),
+            }])
+        );
+        assert_eq!(dark_knight.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn equipment_and_attributes_round_trip() -> Result<(), Error> {
+        let mut db = test_database();
+        let helm = Equipment {
+            slot: GearSlot::Head,
+            name: "Test Helm".to_string(),
+            item_level: Some(700),
+            high_quality: true,
+            materia: vec!["Savage Might Materia XII".to_string()],
+            glamour: None,
+        };
+
+        db.snapshot(test_profile(day(1)))?;
+        let mut geared = test_profile(day(2));
+        geared.equipment = vec![helm.clone()];
+        geared.attributes.set(Attribute::Strength, 5021);
+        let SnapshotOutcome::Created(snapshot_id) = db.snapshot(geared)? else {
+            panic!("new gear should create a snapshot");
+        };
+
+        assert_eq!(db.load_snapshot(snapshot_id)?.equipment, vec![helm.clone()]);
+        let history = db.equipment_history(1)?;
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].captured_at, Some(day(2)));
+        assert_eq!(history[0].equipment, vec![helm]);
+
+        let attributes = db.attribute_history(1)?;
+        assert_eq!(attributes.len(), 1);
+        assert_eq!(attributes[0].attributes.strength, Some(5021));
+
+        Ok(())
+    }
+
+    #[test]
+    fn content_history() -> Result<(), Error> {
+        let mut db = test_database();
+        let eureka = |level, exp| ContentSnapshot {
+            content: Content::Eureka,
+            level: Some(level),
+            exp: Some(exp),
+        };
+
+        db.snapshot(test_profile(day(1)))?;
+        for (d, snapshot) in [(2, eureka(10, (100, 2000))), (3, eureka(11, (0, 2500)))] {
+            let mut profile = test_profile(day(d));
+            profile.content.0.insert(Content::Eureka, snapshot);
+            db.snapshot(profile)?;
         }
+        let mut unchanged = test_profile(day(4));
+        unchanged
+            .content
+            .0
+            .insert(Content::Eureka, eureka(11, (0, 2500)));
+        assert!(matches!(
+            db.snapshot(unchanged)?,
+            SnapshotOutcome::Unchanged(_)
+        ));
+
+        let history = db.content_history(1, None, None)?;
+        let levels: Vec<Option<u64>> = history[&Content::Eureka]
+            .iter()
+            .map(|record| record.level)
+            .collect();
+        assert_eq!(levels, vec![Some(10), Some(11)]);
+        assert_eq!(history[&Content::Eureka][0].exp, Some((100, 2000)));
+        assert!(!history.contains_key(&Content::Bozja));
+
+        let latest = db.snapshots(1, None, None)?.last().unwrap().snapshot_id;
+        assert_eq!(
+            db.load_snapshot(latest)?.content[&Content::Eureka],
+            eureka(11, (0, 2500))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn collections_keep_first_seen() -> Result<(), Error> {
+        let mut db = test_database();
+        let achievement = |achievement_id, earned_at| Achievement {
+            achievement_id,
+            name: format!("Achievement {achievement_id}"),
+            points: 10,
+            earned_at,
+        };
+        let collection =
+            |captured_at, achievements: Vec<Achievement>, mounts: &[&str]| Collection {
+                user_id: 1,
+                captured_at,
+                achievement_points: Some(achievements.iter().map(|a| a.points).sum()),
+                achievements,
+                mounts: mounts.iter().map(|name| name.to_string()).collect(),
+                minions: vec![],
+            };
+
+        let first = db.snapshot_collection(&collection(
+            day(10),
+            vec![achievement(1, Some(day(5)))],
+            &["Company Chocobo"],
+        ))?;
+        assert_eq!(first.achievements.len(), 1);
+        let second = db.snapshot_collection(&collection(
+            day(20),
+            vec![achievement(1, Some(day(5))), achievement(2, None)],
+            &["Company Chocobo", "Fat Chocobo"],
+        ))?;
+        assert_eq!(second.achievements, vec![achievement(2, None)]);
+        assert_eq!(
+            second.collectables,
+            vec![(Collectable::Mount, "Fat Chocobo".to_string())]
+        );
+
+        let achievements = db.achievements(1)?;
+        assert_eq!(achievements[0].first_seen, day(10));
+        assert_eq!(achievements[1].first_seen, day(20));
+        let mounts = db.collectables(1, Collectable::Mount)?;
+        assert_eq!(mounts[0].first_seen, day(10));
+        assert_eq!(mounts[1].first_seen, day(20));
+        assert!(db.collectables(1, Collectable::Minion)?.is_empty());
+
+        let points: Vec<Option<u64>> = db
+            .collection_history(1)?
+            .iter()
+            .map(|record| record.achievement_points)
+            .collect();
+        assert_eq!(points, vec![Some(10), Some(20)]);
+        Ok(())
+    }
+
+    #[test]
+    fn export_copy() -> Result<(), Error> {
+        let mut db = test_database();
+        db.snapshot(test_profile(DateTime::UNIX_EPOCH))?;
+
+        let path = std::env::temp_dir().join(format!(
+            "ffxiv-tracker-export-{}.sqlite",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        db.export(&path)?;
+        let copy = TrackerDatabase::open(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let copy = copy?;
+        assert_eq!(count(&copy, "profile_snapshots"), 1);
+        assert_eq!(
+            count(&copy, "experience_snapshots"),
+            Job::iter().count() as i64
+        );
+        // the copy can't overwrite an existing file
+        assert!(db.export(&std::env::temp_dir()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn free_company_rosters() -> Result<(), Error> {
+        let mut db = test_database();
+        let member = |user_id: u64, name: &str| FreeCompanyMember {
+            user_id,
+            name: name.to_string(),
+            rank: "Member".to_string(),
+        };
+        // lodestone free company IDs overflow a signed 64-bit integer
+        let free_company_id = 9_229_142_273_877_347_048;
+        let roster = |captured_at, members| FreeCompany {
+            free_company_id,
+            captured_at,
+            name: "Test Company".to_string(),
+            tag: "TEST".to_string(),
+            world: "Adamantoise [Aether]".to_string(),
+            grand_company: None,
+            slogan: None,
+            members,
+        };
+
+        db.snapshot_free_company(&roster(day(2), vec![member(2, "B"), member(1, "A")]))?;
+        db.snapshot_free_company(&roster(day(1), vec![member(1, "A")]))?;
+        db.snapshot_free_company(&roster(day(3), vec![]))?;
+
+        let rosters = db.rosters(free_company_id)?;
+        let times: Vec<_> = rosters.iter().map(|roster| roster.captured_at).collect();
+        assert_eq!(times, vec![day(1), day(2), day(3)]);
+        assert_eq!(rosters[1].members, vec![member(1, "A"), member(2, "B")]);
+        assert!(rosters[2].members.is_empty());
+        assert!(db.rosters(1)?.is_empty());
+
         Ok(())
     }
 }
@@ -0,0 +1,414 @@
+use crate::content::{Content, ContentSnapshot};
+use crate::db::{ProfileFields, StoredSnapshot, TrackerDatabase};
+use crate::error::Error;
+use crate::gear;
+use crate::job::{Job, JobSnapshot};
+use chrono::{DateTime, Utc};
+
+/// Changes between two snapshots of the same player.
+#[derive(Debug, PartialEq)]
+pub struct SnapshotDiff {
+    pub before: (i64, Option<DateTime<Utc>>),
+    pub after: (i64, Option<DateTime<Utc>>),
+    pub fields: Vec<FieldChange>,
+    pub jobs: Vec<JobChange>,
+    pub content: Vec<ContentChange>,
+}
+
+/// A profile detail which changed, such as a world transfer or a new grand
+/// company rank.
+#[derive(Debug, PartialEq)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// A job whose level or experience changed.
+#[derive(Debug, PartialEq)]
+pub struct JobChange {
+    pub job: Job,
+    pub before: JobSnapshot,
+    pub after: JobSnapshot,
+    /// Whether the job was equipped in either snapshot, so the change can be
+    /// attributed to playing it.
+    pub played: bool,
+}
+
+/// Special content whose level or experience changed.
+#[derive(Debug, PartialEq)]
+pub struct ContentChange {
+    pub content: Content,
+    pub before: ContentSnapshot,
+    pub after: ContentSnapshot,
+}
+
+impl JobChange {
+    /// Whether the job was unlocked between the two snapshots.
+    pub fn unlocked(&self) -> bool {
+        matches!((self.before.level, self.after.level), (None, Some(_)))
+    }
+
+    /// Number of levels gained, if the job was already unlocked.
+    pub fn levels_gained(&self) -> Option<u64> {
+        match (self.before.level, self.after.level) {
+            (Some(before), Some(after)) => Some(after.saturating_sub(before)),
+            _ => None,
+        }
+    }
+
+    /// Experience gained, including across level boundaries.
+    ///
+    /// `None` for newly unlocked jobs, since some jobs start above level 1.
+    pub fn exp_gained(&self) -> Option<u64> {
+        let before = self.before.total_exp()?;
+        let after = self.after.total_exp()?;
+        Some(after.saturating_sub(before))
+    }
+}
+
+impl SnapshotDiff {
+    /// Compare two snapshots of the same player.
+    pub fn new(before: &StoredSnapshot, after: &StoredSnapshot) -> Result<SnapshotDiff, Error> {
+        if before.user_id != after.user_id {
+            Err(Error::MismatchedSnapshots {
+                from: before.snapshot_id,
+                to: after.snapshot_id,
+            })?;
+        }
+
+        let mut fields = field_changes(&before.fields, &after.fields);
+        // snapshots from before gear was tracked have no item level to compare
+        let item_levels = (
+            gear::average_item_level(&before.equipment),
+            gear::average_item_level(&after.equipment),
+        );
+        if let (Some(before), Some(after)) = item_levels {
+            if before != after {
+                fields.push(FieldChange {
+                    field: "item_level",
+                    before: Some(before.to_string()),
+                    after: Some(after.to_string()),
+                });
+            }
+        }
+
+        let mut jobs: Vec<JobChange> = vec![];
+        for (job, after_job) in &after.jobs {
+            // jobs missing from older snapshots were not yet unlocked
+            let before_job = before.jobs.get(job).cloned().unwrap_or(JobSnapshot {
+                job: *job,
+                level: None,
+                exp: None,
+            });
+            if before_job != *after_job {
+                jobs.push(JobChange {
+                    job: *job,
+                    before: before_job,
+                    after: after_job.clone(),
+                    played: [before.fields.active_job, after.fields.active_job]
+                        .contains(&Some(*job)),
+                });
+            }
+        }
+
+        // snapshots from before special content was tracked have nothing to
+        // compare, but content missing from later snapshots was not unlocked
+        let mut content: Vec<ContentChange> = vec![];
+        if !before.content.is_empty() {
+            for (name, after_content) in &after.content {
+                let before_content = before
+                    .content
+                    .get(name)
+                    .cloned()
+                    .unwrap_or(ContentSnapshot {
+                        content: *name,
+                        level: None,
+                        exp: None,
+                    });
+                if before_content != *after_content {
+                    content.push(ContentChange {
+                        content: *name,
+                        before: before_content,
+                        after: after_content.clone(),
+                    });
+                }
+            }
+        }
+
+        Ok(SnapshotDiff {
+            before: (before.snapshot_id, before.captured_at),
+            after: (after.snapshot_id, after.captured_at),
+            fields,
+            jobs,
+            content,
+        })
+    }
+}
+
+/// Compare two stored snapshots of a player.
+///
+/// `to` defaults to the latest snapshot. `from` defaults to the latest
+/// snapshot captured at or before `since` if given, and otherwise to the
+/// snapshot preceding `to`.
+pub fn diff(
+    database: &TrackerDatabase,
+    user_id: u64,
+    from: Option<i64>,
+    to: Option<i64>,
+    since: Option<DateTime<Utc>>,
+) -> Result<SnapshotDiff, Error> {
+    let snapshots = database.snapshots(user_id, None, None)?;
+    let to = match to {
+        Some(to) => to,
+        None => {
+            snapshots
+                .last()
+                .ok_or(Error::NoSnapshot(format!("of {user_id}")))?
+                .snapshot_id
+        }
+    };
+    let from = match (from, since) {
+        (Some(from), _) => from,
+        (None, Some(since)) => {
+            database
+                .snapshots(user_id, None, Some(since))?
+                .last()
+                .ok_or(Error::NoSnapshot(format!("of {user_id} before {since}")))?
+                .snapshot_id
+        }
+        (None, None) => {
+            let position = snapshots
+                .iter()
+                .position(|snapshot| snapshot.snapshot_id == to)
+                .ok_or(Error::NoSnapshot(format!("{to} of {user_id}")))?;
+            position
+                .checked_sub(1)
+                .map(|previous| snapshots[previous].snapshot_id)
+                .ok_or(Error::NoSnapshot(format!("of {user_id} before {to}")))?
+        }
+    };
+
+    let before = database.load_snapshot(from)?;
+    let after = database.load_snapshot(to)?;
+    for snapshot in [&before, &after] {
+        if snapshot.user_id != user_id {
+            Err(Error::NoSnapshot(format!(
+                "{} of {user_id}",
+                snapshot.snapshot_id
+            )))?;
+        }
+    }
+    SnapshotDiff::new(&before, &after)
+}
+
+fn field_changes(before: &ProfileFields, after: &ProfileFields) -> Vec<FieldChange> {
+    let mut changes: Vec<FieldChange> = vec![];
+    let mut compare = |field: &'static str, before: Option<String>, after: Option<String>| {
+        if before != after {
+            changes.push(FieldChange {
+                field,
+                before,
+                after,
+            });
+        }
+    };
+    compare("name", Some(before.name.clone()), Some(after.name.clone()));
+    compare(
+        "server",
+        Some(before.server.clone()),
+        Some(after.server.clone()),
+    );
+    compare(
+        "free_company",
+        before.free_company.clone(),
+        after.free_company.clone(),
+    );
+    compare(
+        "grand_company",
+        before.grand_company.clone(),
+        after.grand_company.clone(),
+    );
+    compare(
+        "grand_company_rank",
+        before.grand_company_rank.clone(),
+        after.grand_company_rank.clone(),
+    );
+    compare(
+        "city_state",
+        Some(before.city_state.clone()),
+        Some(after.city_state.clone()),
+    );
+    compare(
+        "guardian",
+        Some(before.guardian.clone()),
+        Some(after.guardian.clone()),
+    );
+    compare(
+        "nameday",
+        Some(before.nameday.clone()),
+        Some(after.nameday.clone()),
+    );
+    compare("race", Some(before.race.clone()), Some(after.race.clone()));
+    compare("clan", Some(before.clan.clone()), Some(after.clan.clone()));
+    compare(
+        "gender",
+        Some(before.gender.clone()),
+        Some(after.gender.clone()),
+    );
+    compare(
+        "hp",
+        Some(before.hp.to_string()),
+        Some(after.hp.to_string()),
+    );
+    compare(
+        "mp",
+        Some(before.mp.to_string()),
+        Some(after.mp.to_string()),
+    );
+    compare(
+        "active_job",
+        before.active_job.map(|job| job.to_string()),
+        after.active_job.map(|job| job.to_string()),
+    );
+    compare(
+        "active_level",
+        before.active_level.map(|level| level.to_string()),
+        after.active_level.map(|level| level.to_string()),
+    );
+    changes
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn job(job: Job, level: Option<u64>, exp: Option<(u64, u64)>) -> JobSnapshot {
+        JobSnapshot { job, level, exp }
+    }
+
+    fn stored(snapshot_id: i64, jobs: Vec<JobSnapshot>) -> StoredSnapshot {
+        StoredSnapshot {
+            snapshot_id,
+            user_id: 1,
+            captured_at: None,
+            fields: ProfileFields {
+                free_company: None,
+                free_company_id: None,
+                name: "Test Player".to_string(),
+                nameday: "1st Sun of the 1st Astral Moon".to_string(),
+                guardian: "Halone, the Fury".to_string(),
+                city_state: "Ul'dah".to_string(),
+                server: "Adamantoise [Aether]".to_string(),
+                race: "Lalafell".to_string(),
+                clan: "Dunesfolk".to_string(),
+                gender: "♂".to_string(),
+                grand_company: Some("Immortal Flames".to_string()),
+                grand_company_rank: Some("Flame Private Third Class".to_string()),
+                hp: 100,
+                mp: 100,
+                active_job: None,
+                active_level: None,
+            },
+            attributes: Default::default(),
+            equipment: vec![],
+            content: BTreeMap::new(),
+            jobs: jobs
+                .into_iter()
+                .map(|snapshot| (snapshot.job, snapshot))
+                .collect::<BTreeMap<Job, JobSnapshot>>(),
+        }
+    }
+
+    #[test]
+    fn diff_jobs_and_fields() -> Result<(), Error> {
+        let before = stored(
+            1,
+            vec![
+                job(Job::Paladin, Some(50), Some((1000, 2000))),
+                job(Job::Warrior, Some(30), Some((100, 500))),
+                job(Job::Sage, None, None),
+                job(Job::Miner, Some(10), Some((5, 10))),
+            ],
+        );
+        let mut after = stored(
+            2,
+            vec![
+                job(Job::Paladin, Some(50), Some((1500, 2000))),
+                job(Job::Warrior, Some(32), Some((0, 600))),
+                job(Job::Sage, Some(71), Some((0, 3000))),
+                job(Job::Miner, Some(10), Some((5, 10))),
+            ],
+        );
+        after.fields.server = "Cactuar [Aether]".to_string();
+        after.fields.active_job = Some(Job::Warrior);
+        after.fields.grand_company_rank = Some("Flame Private Second Class".to_string());
+
+        let diff = SnapshotDiff::new(&before, &after)?;
+        assert_eq!(
+            diff.fields,
+            vec![
+                FieldChange {
+                    field: "server",
+                    before: Some("Adamantoise [Aether]".to_string()),
+                    after: Some("Cactuar [Aether]".to_string()),
+                },
+                FieldChange {
+                    field: "grand_company_rank",
+                    before: Some("Flame Private Third Class".to_string()),
+                    after: Some("Flame Private Second Class".to_string()),
+                },
+                FieldChange {
+                    field: "active_job",
+                    before: None,
+                    after: Some("Warrior".to_string()),
+                },
+            ]
+        );
+
+        let jobs: Vec<Job> = diff.jobs.iter().map(|change| change.job).collect();
+        assert_eq!(jobs, vec![Job::Paladin, Job::Warrior, Job::Sage]);
+        assert_eq!(diff.jobs[0].exp_gained(), Some(500));
+        assert_eq!(diff.jobs[0].levels_gained(), Some(0));
+        assert_eq!(diff.jobs[1].levels_gained(), Some(2));
+        assert!(diff.jobs[1].played && !diff.jobs[0].played);
+        // the rest of level 30, then all of level 31
+        assert_eq!(diff.jobs[1].exp_gained(), Some(162_500 - 100 + 175_900));
+        assert!(diff.jobs[2].unlocked());
+        assert_eq!(diff.jobs[2].exp_gained(), None);
+        assert!(diff.content.is_empty());
+
+        let eureka = |level, exp| ContentSnapshot {
+            content: Content::Eureka,
+            level: Some(level),
+            exp: Some(exp),
+        };
+        let mut before = stored(1, vec![]);
+        let mut after = stored(2, vec![]);
+        before
+            .content
+            .insert(Content::Eureka, eureka(10, (100, 2000)));
+        after.content.insert(Content::Eureka, eureka(11, (0, 2500)));
+        after.content.insert(
+            Content::Bozja,
+            ContentSnapshot {
+                content: Content::Bozja,
+                level: Some(1),
+                exp: Some((0, 500)),
+            },
+        );
+        let diff = SnapshotDiff::new(&before, &after)?;
+        let content: Vec<Content> = diff.content.iter().map(|change| change.content).collect();
+        assert_eq!(content, vec![Content::Eureka, Content::Bozja]);
+        assert_eq!(diff.content[1].before.level, None);
+
+        after.user_id = 2;
+        assert!(matches!(
+            SnapshotDiff::new(&before, &after),
+            Err(Error::MismatchedSnapshots { from: 1, to: 2 })
+        ));
+
+        Ok(())
+    }
+}
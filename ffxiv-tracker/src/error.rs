@@ -0,0 +1,139 @@
+use std::path::PathBuf;
+
+use crate::job::Job;
+use rusqlite::ErrorCode;
+
+/// Errors from scraping lodestone, parsing pages, and storing snapshots.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Lodestone responded with an error, such as 404 for a deleted character
+    /// or 503 during maintenance.
+    #[error("{url}: HTTP status {status}")]
+    Http { url: String, status: u16 },
+    /// Lodestone couldn't be reached, or the connection failed mid-response.
+    #[error("{url}: {message}")]
+    Network { url: String, message: String },
+    /// A CSS selector failed to parse.
+    #[error("invalid selector: {0}")]
+    Selector(&'static str),
+    /// An element expected on the page is missing, usually because the page
+    /// layout changed.
+    #[error("couldn't find {field} ({selector})")]
+    MissingElement {
+        field: &'static str,
+        selector: &'static str,
+    },
+    /// An element was found, but its contents couldn't be parsed.
+    #[error("invalid {field} ({selector}): {value:?}")]
+    InvalidElement {
+        field: &'static str,
+        selector: &'static str,
+        value: String,
+    },
+    /// A class or job name which doesn't match any [`Job`].
+    #[error("unknown job: {0}")]
+    UnknownJob(String),
+    /// A job missing from the class/job page.
+    #[error("missing {0:?}")]
+    MissingJob(Job),
+    #[error("{context}: {source}")]
+    Database {
+        context: &'static str,
+        #[source]
+        source: rusqlite::Error,
+    },
+    /// The database was written by a newer release of the tracker.
+    #[error("database schema version {version} is newer than supported version {supported}")]
+    SchemaTooNew { version: usize, supported: usize },
+    /// A value read back from the database which couldn't be parsed.
+    #[error("invalid stored {field}: {value:?}")]
+    InvalidRecord { field: &'static str, value: String },
+    #[error("no snapshot {0}")]
+    NoSnapshot(String),
+    /// Two snapshots to compare belong to different players.
+    #[error("snapshots {from} and {to} are of different players")]
+    MismatchedSnapshots { from: i64, to: i64 },
+    /// A character search found nobody with that name on that world.
+    #[error("no character named {0}")]
+    NoCharacter(String),
+    /// A character search found several characters with exactly that name.
+    #[error("several characters named {name}: {ids:?}")]
+    AmbiguousCharacter { name: String, ids: Vec<u64> },
+    #[error("failed to access {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    /// The API server couldn't listen on the requested address.
+    #[error("failed to listen on {address}: {message}")]
+    Listen { address: String, message: String },
+    #[error("invalid config: {0}")]
+    Config(String),
+    #[error("failed to install signal handler: {0}")]
+    Signal(#[source] ctrlc::Error),
+    /// Some snapshots in a batch failed. Details are reported as they happen.
+    #[error("{failed} of {total} snapshots failed")]
+    Batch { failed: usize, total: usize },
+}
+
+impl Error {
+    /// Wrap a database error, describing what we were doing.
+    pub fn database(context: &'static str) -> impl FnOnce(rusqlite::Error) -> Error {
+        move |source| Error::Database { context, source }
+    }
+
+    /// Whether the same request may succeed if retried later.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::Http { status, .. } => *status == 429 || *status >= 500,
+            Error::Network { .. } => true,
+            Error::Database { source, .. } => matches!(
+                source.sqlite_error_code(),
+                Some(ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked)
+            ),
+            _ => false,
+        }
+    }
+
+    /// Whether lodestone reported that the character doesn't exist.
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, Error::Http { status: 404, .. })
+    }
+}
+
+impl From<ureq::Error> for Error {
+    fn from(error: ureq::Error) -> Self {
+        match error {
+            ureq::Error::Status(status, response) => Error::Http {
+                url: response.get_url().to_string(),
+                status,
+            },
+            ureq::Error::Transport(transport) => Error::Network {
+                url: transport
+                    .url()
+                    .map(|url| url.to_string())
+                    .unwrap_or_default(),
+                message: transport.to_string(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn classify_http_errors() {
+        let http = |status| Error::Http {
+            url: "https://na.finalfantasyxiv.com/lodestone/character/1".to_string(),
+            status,
+        };
+        assert!(http(404).is_not_found());
+        assert!(!http(404).is_retryable());
+        assert!(http(429).is_retryable());
+        assert!(http(503).is_retryable());
+        assert!(!Error::UnknownJob("Freelancer".to_string()).is_retryable());
+    }
+}
@@ -0,0 +1,83 @@
+use crate::job::Job;
+
+/// Experience required to advance from each level to the next, starting at
+/// level 1. This is the `max_xp` shown on the lodestone class/job page for a
+/// job at that level.
+///
+/// All jobs share the same curve, including disciples of the hand and land.
+/// Limited jobs stop partway through it at their own level cap.
+#[rustfmt::skip]
+pub const EXP_TO_NEXT: [u64; 99] = [
+    // 1-10
+    300, 600, 1_100, 1_700, 2_300, 4_200, 6_000, 7_350, 9_930, 11_800,
+    // 11-20
+    15_600, 19_600, 23_700, 26_400, 30_500, 35_400, 40_500, 45_700, 51_000, 56_600,
+    // 21-30
+    63_900, 71_400, 79_100, 87_100, 95_200, 109_800, 124_800, 140_200, 155_900, 162_500,
+    // 31-40
+    175_900, 189_600, 203_500, 217_900, 232_320, 249_900, 267_800, 286_200, 304_900, 324_000,
+    // 41-50
+    340_200, 356_800, 373_700, 390_800, 408_200, 437_600, 467_500, 498_000, 529_000, 864_000,
+    // 51-60
+    1_058_400, 1_267_200, 1_555_200, 1_872_000, 2_217_600,
+    2_592_000, 2_995_200, 3_427_200, 3_888_000, 4_470_000,
+    // 61-70
+    4_873_000, 5_316_000, 5_809_000, 6_364_000, 6_995_000,
+    7_722_000, 8_575_000, 9_593_000, 10_826_000, 12_449_000,
+    // 71-80
+    13_881_000, 15_095_000, 16_136_000, 17_057_000, 17_903_000,
+    18_683_000, 19_401_000, 20_047_000, 20_642_000, 21_196_000,
+    // 81-90
+    21_713_000, 22_194_000, 22_642_000, 23_059_000, 23_446_000,
+    23_806_000, 24_138_000, 24_446_000, 24_728_000, 24_988_000,
+    // 91-99
+    25_228_000, 25_448_000, 25_650_000, 25_836_000, 26_007_000,
+    26_163_000, 26_306_000, 26_437_000, 26_557_000,
+];
+
+/// Total experience required to reach `level` from level 1.
+pub fn exp_to_reach(level: u64) -> Option<u64> {
+    if level == 0 {
+        return None;
+    }
+    let levels = usize::try_from(level - 1).ok()?;
+    if levels > EXP_TO_NEXT.len() {
+        return None;
+    }
+    Some(EXP_TO_NEXT[..levels].iter().sum())
+}
+
+/// Lifetime experience earned on a job, given its level and progress through
+/// that level.
+///
+/// Returns `None` for jobs which haven't been unlocked, or with a level beyond
+/// the experience table.
+pub fn total_exp(job: Job, level: Option<u64>, exp: Option<(u64, u64)>) -> Option<u64> {
+    let level = level?.min(job.level_cap());
+    let current = match exp {
+        Some((current, _)) if level < job.level_cap() => current,
+        _ => 0,
+    };
+    Some(exp_to_reach(level)? + current)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lifetime_exp() {
+        assert_eq!(exp_to_reach(1), Some(0));
+        assert_eq!(exp_to_reach(3), Some(900));
+        assert_eq!(exp_to_reach(100), Some(EXP_TO_NEXT.iter().sum()));
+        assert_eq!(exp_to_reach(101), None);
+
+        assert_eq!(total_exp(Job::Paladin, None, None), None);
+        assert_eq!(
+            total_exp(Job::Paladin, Some(3), Some((50, 1_100))),
+            Some(950)
+        );
+        // experience at the level cap isn't shown, and doesn't count
+        assert_eq!(total_exp(Job::BlueMage, Some(80), None), exp_to_reach(80));
+    }
+}
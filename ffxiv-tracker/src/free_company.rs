@@ -0,0 +1,244 @@
+use crate::error::Error;
+use crate::locale::Language;
+use crate::profile::{fetch, has_next_page, link_id, select_attr, select_text, selector};
+use crate::region::Region;
+use chrono::{DateTime, Utc};
+use scraper::Html;
+
+/// Most member list pages to follow. Free companies are capped at 512
+/// members, shown 50 to a page.
+const MAX_MEMBER_PAGES: usize = 20;
+
+/// A free company and its roster, collected from its lodestone pages.
+#[derive(Debug)]
+pub struct FreeCompany {
+    pub free_company_id: u64,
+    /// When the roster was downloaded from lodestone.
+    pub captured_at: DateTime<Utc>,
+    pub name: String,
+    pub tag: String,
+    pub world: String,
+    pub grand_company: Option<String>,
+    pub slogan: Option<String>,
+    pub members: Vec<FreeCompanyMember>,
+}
+
+/// A member of a free company, as listed on its member pages.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FreeCompanyMember {
+    pub user_id: u64,
+    pub name: String,
+    /// The member's free company rank, such as "Master". Ranks are named by
+    /// each free company.
+    pub rank: String,
+}
+
+/// Members who joined or left a free company between two roster snapshots.
+#[derive(Debug, PartialEq)]
+pub struct RosterChanges {
+    pub joined: Vec<FreeCompanyMember>,
+    pub left: Vec<FreeCompanyMember>,
+}
+
+impl FreeCompany {
+    pub fn get(free_company_id: u64, region: Region) -> Result<FreeCompany, Error> {
+        let base_url = format!("{}/freecompany/{free_company_id}", region.lodestone_url());
+        let page = fetch(&format!("{base_url}/"), &[])?;
+        let captured_at = Utc::now();
+
+        let mut members: Vec<FreeCompanyMember> = vec![];
+        for page_number in 1..=MAX_MEMBER_PAGES {
+            let member_page = fetch(
+                &format!("{base_url}/member/"),
+                &[("page", &page_number.to_string())],
+            )?;
+            let (page_members, more) = parse_members(&member_page)?;
+            members.extend(page_members);
+            if !more {
+                break;
+            }
+        }
+
+        Self::parse(
+            free_company_id,
+            captured_at,
+            Language::from(region),
+            &page,
+            members,
+        )
+    }
+
+    fn parse(
+        free_company_id: u64,
+        captured_at: DateTime<Utc>,
+        language: Language,
+        page: &Html,
+        members: Vec<FreeCompanyMember>,
+    ) -> Result<FreeCompany, Error> {
+        const NAME: &str = "p.entry__freecompany__name";
+        // the grand company and the world share a class, in that order
+        const GC_WORLD: &str = "p.entry__freecompany__gc";
+        const TAG: &str = "p.freecompany__text__tag";
+        const SLOGAN: &str = "p.freecompany__text__message";
+
+        let root = page.root_element();
+        let name = select_text(root, NAME, "free company name")?;
+        let gc_world: Vec<String> = root
+            .select(&selector(GC_WORLD)?)
+            .map(|element| element.text().collect::<String>().trim().to_string())
+            .collect();
+        let (grand_company, world) = match &gc_world[..] {
+            [world] => (None, world.clone()),
+            [grand_company, world] => {
+                // shown with the company's standing, e.g. "Maelstrom <Friendly>"
+                let grand_company = grand_company.split(" <").next().unwrap_or_default();
+                (
+                    Some(language.locale().english(grand_company)),
+                    world.clone(),
+                )
+            }
+            _ => Err(Error::MissingElement {
+                field: "free company world",
+                selector: GC_WORLD,
+            })?,
+        };
+        let tag = select_text(root, TAG, "free company tag")?
+            .trim()
+            .trim_start_matches('«')
+            .trim_end_matches('»')
+            .to_string();
+        let slogan = root
+            .select(&selector(SLOGAN)?)
+            .next()
+            .map(|element| element.text().collect::<Vec<&str>>().join("\n"))
+            .filter(|slogan| !slogan.trim().is_empty());
+
+        Ok(FreeCompany {
+            free_company_id,
+            captured_at,
+            name,
+            tag,
+            world,
+            grand_company,
+            slogan,
+            members,
+        })
+    }
+}
+
+/// Parse one page of a free company's member list, and whether there are more
+/// pages after it.
+fn parse_members(page: &Html) -> Result<(Vec<FreeCompanyMember>, bool), Error> {
+    const MEMBER: &str = "div.ldst__window li.entry";
+    const LINK: &str = "a.entry__bg";
+    const NAME: &str = "p.entry__name";
+    const RANK: &str = "ul.entry__freecompany__info li span";
+
+    let mut members: Vec<FreeCompanyMember> = vec![];
+    for entry in page.select(&selector(MEMBER)?) {
+        let href = select_attr(entry, LINK, "href", "member link")?;
+        let user_id = link_id(&href).ok_or(Error::InvalidElement {
+            field: "member link",
+            selector: LINK,
+            value: href.clone(),
+        })?;
+        members.push(FreeCompanyMember {
+            user_id,
+            name: select_text(entry, NAME, "member name")?,
+            rank: select_text(entry, RANK, "member rank")?.trim().to_string(),
+        });
+    }
+
+    let more = has_next_page(page)? && !members.is_empty();
+    Ok((members, more))
+}
+
+/// Compare two rosters of the same free company.
+pub fn roster_changes(before: &[FreeCompanyMember], after: &[FreeCompanyMember]) -> RosterChanges {
+    let missing_from = |roster: &[FreeCompanyMember], member: &FreeCompanyMember| {
+        !roster.iter().any(|other| other.user_id == member.user_id)
+    };
+    RosterChanges {
+        joined: after
+            .iter()
+            .filter(|member| missing_from(before, member))
+            .cloned()
+            .collect(),
+        left: before
+            .iter()
+            .filter(|member| missing_from(after, member))
+            .cloned()
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_free_company() -> Result<(), Error> {
+        let page = Html::parse_document(
+            r#"
+            <div class="entry__freecompany__box">
+              <p class="entry__freecompany__gc">Maelstrom &lt;Friendly&gt;</p>
+              <p class="entry__freecompany__name">Test Company</p>
+              <p class="entry__freecompany__gc"><i class="xiv-lds"></i>Adamantoise [Aether]</p>
+            </div>
+            <p class="freecompany__text freecompany__text__tag">«TEST»</p>
+            <p class="freecompany__text freecompany__text__message">Hello<br>World</p>
+            "#,
+        );
+        let members = Html::parse_document(
+            r#"
+            <div class="ldst__window">
+              <ul>
+                <li class="entry">
+                  <a href="/lodestone/character/38598907/" class="entry__bg">
+                    <p class="entry__name">Test Player</p>
+                    <ul class="entry__freecompany__info"><li><img src="rank.png"><span>Master</span></li></ul>
+                  </a>
+                </li>
+              </ul>
+              <ul class="btn__pager">
+                <li><a href="https://na.finalfantasyxiv.com/lodestone/freecompany/1/member/?page=2" class="btn__pager__next"></a></li>
+              </ul>
+            </div>
+            "#,
+        );
+
+        let (members, more) = parse_members(&members)?;
+        assert!(more);
+        let free_company = FreeCompany::parse(
+            1,
+            DateTime::UNIX_EPOCH,
+            Language::English,
+            &page,
+            members.clone(),
+        )?;
+        assert_eq!(free_company.name, "Test Company");
+        assert_eq!(free_company.tag, "TEST");
+        assert_eq!(free_company.world, "Adamantoise [Aether]");
+        assert_eq!(free_company.grand_company.as_deref(), Some("Maelstrom"));
+        assert_eq!(free_company.slogan.as_deref(), Some("Hello\nWorld"));
+        assert_eq!(
+            free_company.members,
+            vec![FreeCompanyMember {
+                user_id: 38598907,
+                name: "Test Player".to_string(),
+                rank: "Master".to_string(),
+            }]
+        );
+
+        let newcomer = FreeCompanyMember {
+            user_id: 26351638,
+            name: "New Player".to_string(),
+            rank: "Recruit".to_string(),
+        };
+        let changes = roster_changes(&members, std::slice::from_ref(&newcomer));
+        assert_eq!(changes.joined, vec![newcomer]);
+        assert_eq!(changes.left, members);
+
+        Ok(())
+    }
+}
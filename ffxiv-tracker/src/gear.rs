@@ -0,0 +1,223 @@
+use crate::error::Error;
+use crate::profile::{select_text, selector};
+use scraper::ElementRef;
+use strum::{Display, EnumString};
+use tracing::{event, Level};
+
+/// Equipment slots, in the order lodestone lists them.
+#[derive(Debug, Display, EnumString, Eq, Hash, PartialEq, Clone, Copy, PartialOrd, Ord)]
+#[strum(serialize_all = "title_case")]
+pub enum GearSlot {
+    MainHand,
+    OffHand,
+    Head,
+    Body,
+    Hands,
+    Legs,
+    Feet,
+    Earrings,
+    Necklace,
+    Bracelets,
+    LeftRing,
+    RightRing,
+    SoulCrystal,
+}
+
+impl GearSlot {
+    /// The slot shown in lodestone's `icon-c--{index}` gear box. Index 5 was
+    /// the waist slot, which has been removed from the game.
+    fn from_index(index: usize) -> Option<GearSlot> {
+        let slot = match index {
+            0 => GearSlot::MainHand,
+            1 => GearSlot::OffHand,
+            2 => GearSlot::Head,
+            3 => GearSlot::Body,
+            4 => GearSlot::Hands,
+            6 => GearSlot::Legs,
+            7 => GearSlot::Feet,
+            8 => GearSlot::Earrings,
+            9 => GearSlot::Necklace,
+            10 => GearSlot::Bracelets,
+            11 => GearSlot::LeftRing,
+            12 => GearSlot::RightRing,
+            13 => GearSlot::SoulCrystal,
+            _ => return None,
+        };
+        Some(slot)
+    }
+}
+
+/// An equipped item. Names are as shown on the lodestone site the profile was
+/// fetched from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Equipment {
+    pub slot: GearSlot,
+    pub name: String,
+    pub item_level: Option<u64>,
+    pub high_quality: bool,
+    /// Names of melded materia, in socket order.
+    pub materia: Vec<String>,
+    /// The item this one is glamoured as, if any.
+    pub glamour: Option<String>,
+}
+
+/// Average item level, as shown in game.
+///
+/// The soul crystal isn't counted, and a two-handed weapon (or a main hand
+/// with no off hand) counts twice.
+pub fn average_item_level(equipment: &[Equipment]) -> Option<u64> {
+    let level = |slot: GearSlot| {
+        equipment
+            .iter()
+            .find(|item| item.slot == slot)
+            .and_then(|item| item.item_level)
+    };
+    let main_hand = level(GearSlot::MainHand)?;
+    let mut total = main_hand + level(GearSlot::OffHand).unwrap_or(main_hand);
+    for slot in [
+        GearSlot::Head,
+        GearSlot::Body,
+        GearSlot::Hands,
+        GearSlot::Legs,
+        GearSlot::Feet,
+        GearSlot::Earrings,
+        GearSlot::Necklace,
+        GearSlot::Bracelets,
+        GearSlot::LeftRing,
+        GearSlot::RightRing,
+    ] {
+        total += level(slot).unwrap_or(0);
+    }
+    Some(total / 12)
+}
+
+/// Parse the equipment shown on a profile page.
+pub fn parse(profile: ElementRef) -> Result<Vec<Equipment>, Error> {
+    const ITEM: &str = "div.character__detail div.item_detail_box";
+    const NAME: &str = "h2.db-tooltip__item__name";
+    const HQ_ICON: &str = "h2.db-tooltip__item__name img";
+    const ITEM_LEVEL: &str = "div.db-tooltip__item__level";
+    const MATERIA: &str = "ul.db-tooltip__materia div.db-tooltip__materia__txt";
+    const GLAMOUR: &str = "div.db-tooltip__item__mirage p";
+    /// Private use character rendered by the game font as the HQ icon.
+    const HQ_GLYPH: char = '\u{e03c}';
+
+    let mut equipment: Vec<Equipment> = vec![];
+    for item in profile.select(&selector(ITEM)?) {
+        // each item sits in a gear box whose class gives its slot
+        let Some(index) = item.ancestors().find_map(|node| {
+            node.value()
+                .as_element()?
+                .classes()
+                .find_map(|class| class.strip_prefix("icon-c--")?.parse::<usize>().ok())
+        }) else {
+            continue;
+        };
+        // a new slot shouldn't lose the rest of the profile
+        let Some(slot) = GearSlot::from_index(index) else {
+            event!(
+                Level::WARN,
+                "skipping item in unknown gear slot icon-c--{index}"
+            );
+            continue;
+        };
+
+        let name = select_text(item, NAME, "item name")?;
+        let high_quality =
+            name.contains(HQ_GLYPH) || item.select(&selector(HQ_ICON)?).next().is_some();
+        let item_level = item
+            .select(&selector(ITEM_LEVEL)?)
+            .next()
+            .map(|element| {
+                element
+                    .text()
+                    .collect::<String>()
+                    .chars()
+                    .filter(char::is_ascii_digit)
+                    .collect::<String>()
+            })
+            .and_then(|digits| digits.parse::<u64>().ok());
+        let materia = item
+            .select(&selector(MATERIA)?)
+            .filter_map(|element| element.text().next())
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect();
+        let glamour = item
+            .select(&selector(GLAMOUR)?)
+            .next()
+            .map(|element| element.text().collect::<String>().trim().to_string())
+            .filter(|glamour| !glamour.is_empty());
+
+        equipment.push(Equipment {
+            slot,
+            name: name.replace(HQ_GLYPH, "").trim().to_string(),
+            item_level,
+            high_quality,
+            materia,
+            glamour,
+        });
+    }
+    equipment.sort_by_key(|item| item.slot);
+    Ok(equipment)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use scraper::Html;
+
+    #[test]
+    fn parse_equipment() -> Result<(), Error> {
+        let html = Html::parse_document(
+            r#"
+            <div class="character__detail">
+              <div class="icon-c--0"><div class="item_detail_box">
+                <h2 class="db-tooltip__item__name">Test Greatsword</h2>
+                <div class="db-tooltip__item__level">Item Level 710</div>
+                <ul class="db-tooltip__materia">
+                  <li><div class="db-tooltip__materia__txt">Savage Might Materia XII<span>Determination +54</span></div></li>
+                  <li class="db-tooltip__materia__normal"><div class="db-tooltip__materia__txt"></div></li>
+                </ul>
+                <div class="db-tooltip__item__mirage"><p>Ultimate Omega Sword</p></div>
+              </div></div>
+              <div class="icon-c--2"><div class="item_detail_box">
+                <h2 class="db-tooltip__item__name">Test Helm&#xe03c;</h2>
+                <div class="db-tooltip__item__level">Item Level 700</div>
+              </div></div>
+              <div class="icon-c--13"><div class="item_detail_box">
+                <h2 class="db-tooltip__item__name">Soul of the Warrior</h2>
+                <div class="db-tooltip__item__level">Item Level 30</div>
+              </div></div>
+              <div class="icon-c--20"><div class="item_detail_box">
+                <h2 class="db-tooltip__item__name">Test Future Slot</h2>
+              </div></div>
+            </div>
+            "#,
+        );
+
+        let equipment = parse(html.root_element())?;
+        assert_eq!(
+            equipment[0],
+            Equipment {
+                slot: GearSlot::MainHand,
+                name: "Test Greatsword".to_string(),
+                item_level: Some(710),
+                high_quality: false,
+                materia: vec!["Savage Might Materia XII".to_string()],
+                glamour: Some("Ultimate Omega Sword".to_string()),
+            }
+        );
+        assert_eq!(equipment[1].slot, GearSlot::Head);
+        assert_eq!(equipment[1].name, "Test Helm");
+        assert!(equipment[1].high_quality);
+        assert_eq!(equipment[2].slot, GearSlot::SoulCrystal);
+        assert_eq!(equipment.len(), 3);
+
+        // the two-handed weapon counts twice, and missing gear counts as zero
+        assert_eq!(average_item_level(&equipment), Some((710 * 2 + 700) / 12));
+        assert_eq!(average_item_level(&equipment[1..]), None);
+
+        Ok(())
+    }
+}
@@ -1,3 +1,5 @@
+use crate::error::Error;
+use crate::experience;
 use std::collections::BTreeMap;
 use strum::{Display, EnumIter, EnumString, IntoEnumIterator};
 
@@ -64,7 +66,48 @@ pub enum Job {
     Fisher,
 }
 
-#[derive(Debug)]
+/// Groups of jobs, as laid out in [`Job`]. Melee, ranged and caster jobs are
+/// all DPS.
+#[derive(Debug, Display, EnumIter, Eq, Hash, PartialEq, Clone, Copy, PartialOrd, Ord)]
+pub enum Role {
+    Tank,
+    Healer,
+    #[strum(to_string = "DPS")]
+    Dps,
+    #[strum(to_string = "Disciple of the Hand")]
+    DiscipleOfHand,
+    #[strum(to_string = "Disciple of the Land")]
+    DiscipleOfLand,
+}
+
+impl Job {
+    pub fn role(&self) -> Role {
+        match self {
+            Job::Paladin | Job::Warrior | Job::DarkKnight | Job::Gunbreaker => Role::Tank,
+            Job::WhiteMage | Job::Scholar | Job::Astrologian | Job::Sage => Role::Healer,
+            Job::Carpenter
+            | Job::Armorer
+            | Job::Blacksmith
+            | Job::Goldsmith
+            | Job::Leatherworker
+            | Job::Weaver
+            | Job::Alchemist
+            | Job::Culinarian => Role::DiscipleOfHand,
+            Job::Miner | Job::Botanist | Job::Fisher => Role::DiscipleOfLand,
+            _ => Role::Dps,
+        }
+    }
+
+    /// The maximum level of the job in the current expansion.
+    pub fn level_cap(&self) -> u64 {
+        match self {
+            Job::BlueMage => 80,
+            _ => 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 /// A snapshot of a job's experience level taken from a lodestone profile.
 pub struct JobSnapshot {
     pub job: Job,
@@ -72,12 +115,25 @@ pub struct JobSnapshot {
     pub exp: Option<(u64, u64)>,
 }
 
+impl JobSnapshot {
+    /// Lifetime experience earned on this job, or `None` if it isn't unlocked.
+    pub fn total_exp(&self) -> Option<u64> {
+        experience::total_exp(self.job, self.level, self.exp)
+    }
+
+    /// Progress towards the level cap, from 0 to 100.
+    pub fn percent_to_cap(&self) -> Option<f64> {
+        let cap = experience::exp_to_reach(self.job.level_cap())?;
+        Some(self.total_exp()? as f64 / cap as f64 * 100.0)
+    }
+}
+
 /// Snapshot of all jobs.
 #[derive(Debug)]
 pub struct PlayerJobSnapshot(pub BTreeMap<Job, JobSnapshot>);
 
 impl TryFrom<Vec<JobSnapshot>> for PlayerJobSnapshot {
-    type Error = String;
+    type Error = Error;
 
     fn try_from(snapshots: Vec<JobSnapshot>) -> Result<Self, Self::Error> {
         let mut jobs: BTreeMap<Job, JobSnapshot> = BTreeMap::new();
@@ -88,10 +144,33 @@ impl TryFrom<Vec<JobSnapshot>> for PlayerJobSnapshot {
         // check for each job before constructing
         for job in Job::iter() {
             if !jobs.contains_key(&job) {
-                Err(format!("missing {job:?}"))?;
+                Err(Error::MissingJob(job))?;
             }
         }
 
         Ok(PlayerJobSnapshot(jobs))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn roles_and_caps() {
+        assert_eq!(Job::Gunbreaker.role(), Role::Tank);
+        assert_eq!(Job::Sage.role(), Role::Healer);
+        assert_eq!(Job::Viper.role(), Role::Dps);
+        assert_eq!(Job::BlueMage.role(), Role::Dps);
+        assert_eq!(Job::Culinarian.role(), Role::DiscipleOfHand);
+        assert_eq!(Job::Fisher.role(), Role::DiscipleOfLand);
+        // initial classes share their job's role
+        assert_eq!(Job::from_str("Arcanist").unwrap().role(), Role::Dps);
+
+        assert_eq!(Job::BlueMage.level_cap(), 80);
+        assert_eq!(Job::Pictomancer.level_cap(), 100);
+        // every cap is covered by the experience table
+        assert!(Job::iter().all(|job| experience::exp_to_reach(job.level_cap()).is_some()));
+    }
+}
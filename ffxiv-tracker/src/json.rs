@@ -0,0 +1,211 @@
+use std::collections::BTreeMap;
+
+use crate::attributes::Attribute;
+use crate::content::Content;
+use crate::db::{ExperienceRecord, StoredSnapshot};
+use crate::diff::SnapshotDiff;
+use crate::experience;
+use crate::gear;
+use crate::job::Job;
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use strum::IntoEnumIterator;
+
+/// Experience history per job, then per special content, keyed by name.
+pub fn history(
+    history: &BTreeMap<Job, Vec<ExperienceRecord>>,
+    content: &BTreeMap<Content, Vec<ExperienceRecord>>,
+) -> Value {
+    let record_json = |record: &ExperienceRecord, total_xp: Option<u64>| {
+        json!({
+            "captured_at": record.captured_at.map(|t| t.to_rfc3339()),
+            "level": record.level,
+            "current_xp": record.exp.map(|(current, _)| current),
+            "max_xp": record.exp.map(|(_, max)| max),
+            "total_xp": total_xp,
+        })
+    };
+    let mut json: serde_json::Map<String, Value> = history
+        .iter()
+        .map(|(job, records)| {
+            let records = records
+                .iter()
+                .map(|record| {
+                    record_json(
+                        record,
+                        experience::total_exp(*job, record.level, record.exp),
+                    )
+                })
+                .collect();
+            (job.to_string(), Value::Array(records))
+        })
+        .collect();
+    for (name, records) in content {
+        let records = records
+            .iter()
+            .map(|record| record_json(record, None))
+            .collect();
+        json.insert(name.to_string(), Value::Array(records));
+    }
+    Value::Object(json)
+}
+
+/// A stored snapshot, with every detail that was recorded.
+pub fn snapshot(snapshot: &StoredSnapshot) -> Value {
+    let fields = &snapshot.fields;
+    let attributes: serde_json::Map<String, Value> = Attribute::iter()
+        .filter_map(|attribute| {
+            let value = snapshot.attributes.get(attribute)?;
+            Some((attribute.to_string(), json!(value)))
+        })
+        .collect();
+    let equipment: Vec<Value> = snapshot
+        .equipment
+        .iter()
+        .map(|item| {
+            json!({
+                "slot": item.slot.to_string(),
+                "name": item.name,
+                "item_level": item.item_level,
+                "high_quality": item.high_quality,
+                "materia": item.materia,
+                "glamour": item.glamour,
+            })
+        })
+        .collect();
+    let jobs: serde_json::Map<String, Value> = snapshot
+        .jobs
+        .values()
+        .map(|job| {
+            let value = json!({
+                "level": job.level,
+                "current_xp": job.exp.map(|(current, _)| current),
+                "max_xp": job.exp.map(|(_, max)| max),
+                "total_xp": job.total_exp(),
+            });
+            (job.job.to_string(), value)
+        })
+        .collect();
+    let content: serde_json::Map<String, Value> = snapshot
+        .content
+        .values()
+        .map(|content| {
+            let value = json!({
+                "level": content.level,
+                "current_xp": content.exp.map(|(current, _)| current),
+                "max_xp": content.exp.map(|(_, max)| max),
+            });
+            (content.content.to_string(), value)
+        })
+        .collect();
+
+    json!({
+        "snapshot_id": snapshot.snapshot_id,
+        "user_id": snapshot.user_id,
+        "captured_at": snapshot.captured_at.map(|t| t.to_rfc3339()),
+        "name": fields.name,
+        "server": fields.server,
+        "free_company": fields.free_company,
+        // free company IDs are too large for JSON numbers in browsers
+        "free_company_id": fields.free_company_id.map(|id| id.to_string()),
+        "nameday": fields.nameday,
+        "guardian": fields.guardian,
+        "city_state": fields.city_state,
+        "race": fields.race,
+        "clan": fields.clan,
+        "gender": fields.gender,
+        "grand_company": fields.grand_company,
+        "grand_company_rank": fields.grand_company_rank,
+        "hp": fields.hp,
+        "mp": fields.mp,
+        "active_job": fields.active_job.map(|job| job.to_string()),
+        "active_level": fields.active_level,
+        "item_level": gear::average_item_level(&snapshot.equipment),
+        "attributes": attributes,
+        "equipment": equipment,
+        "jobs": jobs,
+        "content": content,
+    })
+}
+
+/// Changes between two snapshots.
+pub fn diff(diff: &SnapshotDiff) -> Value {
+    let snapshot = |(snapshot_id, captured_at): (i64, Option<DateTime<Utc>>)| {
+        json!({
+            "snapshot_id": snapshot_id,
+            "captured_at": captured_at.map(|t| t.to_rfc3339()),
+        })
+    };
+    let fields: Vec<Value> = diff
+        .fields
+        .iter()
+        .map(|change| {
+            json!({
+                "field": change.field,
+                "before": change.before,
+                "after": change.after,
+            })
+        })
+        .collect();
+    let jobs: Vec<Value> = diff
+        .jobs
+        .iter()
+        .map(|change| {
+            json!({
+                "job": change.job.to_string(),
+                "before_level": change.before.level,
+                "after_level": change.after.level,
+                "unlocked": change.unlocked(),
+                "levels_gained": change.levels_gained(),
+                "exp_gained": change.exp_gained(),
+                "played": change.played,
+            })
+        })
+        .collect();
+    let content: Vec<Value> = diff
+        .content
+        .iter()
+        .map(|change| {
+            json!({
+                "content": change.content.to_string(),
+                "before_level": change.before.level,
+                "after_level": change.after.level,
+                "before_xp": change.before.exp.map(|(current, _)| current),
+                "after_xp": change.after.exp.map(|(current, _)| current),
+            })
+        })
+        .collect();
+
+    json!({
+        "before": snapshot(diff.before),
+        "after": snapshot(diff.after),
+        "fields": fields,
+        "jobs": jobs,
+        "content": content,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn history_with_content() {
+        let record = |level, exp| ExperienceRecord {
+            captured_at: Some(DateTime::UNIX_EPOCH),
+            level: Some(level),
+            exp: Some(exp),
+        };
+        let jobs = BTreeMap::from([(Job::Paladin, vec![record(3, (50, 1_100))])]);
+        let content = BTreeMap::from([(Content::Eureka, vec![record(10, (100, 2_000))])]);
+
+        let json = history(&jobs, &content);
+        assert_eq!(json["Paladin"][0]["total_xp"], 950);
+        assert_eq!(
+            json["Paladin"][0]["captured_at"],
+            "1970-01-01T00:00:00+00:00"
+        );
+        assert_eq!(json["Eureka"][0]["level"], 10);
+        assert_eq!(json["Eureka"][0]["total_xp"], Value::Null);
+    }
+}
@@ -1,4 +1,24 @@
 #![allow(unused)]
 
+pub(crate) mod attributes;
+pub(crate) mod bulk;
+pub(crate) mod collection;
+pub(crate) mod config;
+pub(crate) mod content;
+pub(crate) mod daemon;
+pub(crate) mod db;
+pub(crate) mod diff;
+pub(crate) mod error;
+pub(crate) mod experience;
+pub(crate) mod free_company;
+pub(crate) mod gear;
 pub(crate) mod job;
+pub(crate) mod json;
+pub(crate) mod locale;
 pub(crate) mod profile;
+pub mod region;
+pub(crate) mod render;
+pub(crate) mod search;
+pub(crate) mod server;
+pub(crate) mod site;
+pub(crate) mod stats;
@@ -0,0 +1,748 @@
+use crate::attributes::Attribute;
+use crate::content::Content;
+use crate::job::Job;
+use crate::region::Region;
+use std::str::FromStr;
+
+/// Language of a lodestone site's pages.
+///
+/// Profiles are always stored in English, so that a character fetched from any
+/// site produces the same snapshot. Values without a known translation are kept
+/// as shown on the page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English,
+    French,
+    German,
+    Japanese,
+}
+
+/// A heading of a block on the profile page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Heading {
+    RaceClanGender,
+    Guardian,
+    CityState,
+    GrandCompany,
+}
+
+/// Text and selectors which differ between languages.
+#[derive(Debug)]
+pub struct Locale {
+    headings: &'static [(&'static str, Heading)],
+    pub hp: &'static str,
+    pub mp: &'static str,
+    pub cp: &'static str,
+    pub gp: &'static str,
+    /// Localized attribute names.
+    attributes: &'static [(&'static str, Attribute)],
+    /// Prefix and suffix around the job name in soul crystal names.
+    ///
+    /// Languages which inflect job names in item names have none, and the
+    /// localized name is searched for instead.
+    soul_crystals: &'static [(&'static str, &'static str)],
+    /// Prefixes and suffixes around the class or job name in main hand weapon
+    /// categories. As with soul crystals, these may be left out.
+    weapons: &'static [(&'static str, &'static str)],
+    /// Localized class and job names.
+    jobs: &'static [(&'static str, Job)],
+    /// Localized names of special content progression, such as "Elemental
+    /// Level".
+    content: &'static [(&'static str, Content)],
+    /// Localized races, clans, guardians, city-states, grand companies and
+    /// grand company ranks, with their English names.
+    values: &'static [(&'static str, &'static str)],
+    /// Words marking astral and umbral moons in namedays.
+    moons: (&'static str, &'static str),
+    /// Whether namedays give the moon before the sun, as in `星3月5日`.
+    month_first: bool,
+}
+
+impl From<Region> for Language {
+    fn from(region: Region) -> Language {
+        match region {
+            Region::Na | Region::Eu => Language::English,
+            Region::Fr => Language::French,
+            Region::De => Language::German,
+            Region::Jp => Language::Japanese,
+        }
+    }
+}
+
+impl Language {
+    pub fn locale(&self) -> &'static Locale {
+        match self {
+            Language::English => &ENGLISH,
+            Language::French => &FRENCH,
+            Language::German => &GERMAN,
+            Language::Japanese => &JAPANESE,
+        }
+    }
+}
+
+impl Locale {
+    /// Identify a profile block by its heading, ignoring case and whitespace.
+    pub fn heading(&self, text: &str) -> Option<Heading> {
+        let text = normalize(text);
+        self.headings
+            .iter()
+            .find(|(heading, _)| normalize(heading) == text)
+            .map(|(_, heading)| *heading)
+    }
+
+    /// Parse a class or job name, accepting English names in any language.
+    pub fn job(&self, name: &str) -> Option<Job> {
+        let name = name.trim();
+        self.jobs
+            .iter()
+            .find(|(localized, _)| localized.to_lowercase() == name.to_lowercase())
+            .map(|(_, job)| *job)
+            .or_else(|| Job::from_str(name).ok())
+    }
+
+    /// Parse a special content progression name, accepting English names in
+    /// any language.
+    pub fn content(&self, name: &str) -> Option<Content> {
+        let name = name.trim();
+        self.content
+            .iter()
+            .find(|(localized, _)| localized.to_lowercase() == name.to_lowercase())
+            .map(|(_, content)| *content)
+            .or_else(|| Content::from_str(name).ok())
+    }
+
+    /// Parse an attribute name, accepting English names in any language.
+    pub fn attribute(&self, name: &str) -> Option<Attribute> {
+        let name = name.trim();
+        self.attributes
+            .iter()
+            .find(|(localized, _)| localized.to_lowercase() == name.to_lowercase())
+            .map(|(_, attribute)| *attribute)
+            .or_else(|| Attribute::from_str(name).ok())
+    }
+
+    /// The job a character has equipped, from the name of their soul crystal,
+    /// or failing that the category of their main hand weapon.
+    pub fn active_job(&self, soul_crystal: Option<&str>, weapon: Option<&str>) -> Option<Job> {
+        let find = |text: &str, affixes: &[(&str, &str)]| {
+            let text = text.trim().replace('\u{2019}', "'");
+            affixes
+                .iter()
+                .find_map(|(prefix, suffix)| {
+                    let name = text.strip_prefix(prefix)?.strip_suffix(suffix)?;
+                    self.job(name)
+                })
+                .or_else(|| self.job_within(&text))
+        };
+        soul_crystal
+            .and_then(|name| find(name, self.soul_crystals))
+            .or_else(|| weapon.and_then(|category| find(category, self.weapons)))
+    }
+
+    /// The job whose localized name appears in `text`, such as "Weißmagier" in
+    /// "Seelenkristall des Weißmagiers". The longest name wins, so that a job
+    /// isn't mistaken for one whose name is part of its own.
+    fn job_within(&self, text: &str) -> Option<Job> {
+        let text = text.to_lowercase();
+        self.jobs
+            .iter()
+            .filter(|(localized, _)| text.contains(&localized.to_lowercase()))
+            .max_by_key(|(localized, _)| localized.chars().count())
+            .map(|(_, job)| *job)
+    }
+
+    /// The English name of a profile value, or the value itself if it has no
+    /// known translation.
+    pub fn english(&self, value: &str) -> String {
+        let value = value.trim();
+        self.values
+            .iter()
+            .find(|(localized, _)| localized.to_lowercase() == value.to_lowercase())
+            .map(|(_, english)| english.to_string())
+            .unwrap_or(value.to_string())
+    }
+
+    /// The English form of a nameday, such as "5th Sun of the 3rd Astral
+    /// Moon", or the nameday itself if it can't be read.
+    pub fn nameday(&self, text: &str) -> String {
+        let text = text.trim();
+        let numbers: Vec<u64> = text
+            .split(|c: char| !c.is_ascii_digit())
+            .filter_map(|part| part.parse().ok())
+            .collect();
+        let lowercase = text.to_lowercase();
+        let (astral, umbral) = self.moons;
+        let moon = if lowercase.contains(&astral.to_lowercase()) {
+            "Astral"
+        } else if lowercase.contains(&umbral.to_lowercase()) {
+            "Umbral"
+        } else {
+            return text.to_string();
+        };
+        let (sun, month) = match numbers[..] {
+            [month, sun] if self.month_first => (sun, month),
+            [sun, month] => (sun, month),
+            _ => return text.to_string(),
+        };
+        format!("{} Sun of the {} {moon} Moon", ordinal(sun), ordinal(month))
+    }
+}
+
+/// An English ordinal number, such as "1st" or "12th".
+fn ordinal(n: u64) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{n}{suffix}")
+}
+
+fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+const ENGLISH: Locale = Locale {
+    headings: &[
+        ("Race/Clan/Gender", Heading::RaceClanGender),
+        ("Guardian", Heading::Guardian),
+        ("City-state", Heading::CityState),
+        ("Grand Company", Heading::GrandCompany),
+    ],
+    hp: "p.character__param__text__hp--en-us + span",
+    mp: "p.character__param__text__mp--en-us + span",
+    cp: "p.character__param__text__cp--en-us + span",
+    gp: "p.character__param__text__gp--en-us + span",
+    attributes: &[],
+    soul_crystals: &[("Soul of the ", "")],
+    weapons: &[
+        ("Two-handed ", "'s Arm"),
+        ("One-handed ", "'s Arm"),
+        ("", "'s Arm"),
+        ("", "'s Grimoire"),
+        ("", "'s Primary Tool"),
+    ],
+    jobs: &[],
+    content: &[],
+    values: &[],
+    moons: ("Astral", "Umbral"),
+    month_first: false,
+};
+
+const FRENCH: Locale = Locale {
+    headings: &[
+        ("Race / Ethnie / Sexe", Heading::RaceClanGender),
+        ("Divinité", Heading::Guardian),
+        ("Cité de départ", Heading::CityState),
+        ("Grande compagnie", Heading::GrandCompany),
+    ],
+    hp: "p.character__param__text__hp--fr + span",
+    mp: "p.character__param__text__mp--fr + span",
+    cp: "p.character__param__text__cp--fr + span",
+    gp: "p.character__param__text__gp--fr + span",
+    attributes: &[
+        ("Force", Attribute::Strength),
+        ("Dextérité", Attribute::Dexterity),
+        ("Vitalité", Attribute::Vitality),
+        ("Intelligence", Attribute::Intelligence),
+        ("Esprit", Attribute::Mind),
+        ("Critique", Attribute::CriticalHitRate),
+        ("Détermination", Attribute::Determination),
+        ("Coup direct", Attribute::DirectHitRate),
+        ("Défense", Attribute::Defense),
+        ("Défense magique", Attribute::MagicDefense),
+        ("Puissance d'attaque", Attribute::AttackPower),
+        ("Vivacité", Attribute::SkillSpeed),
+        ("Puissance magique", Attribute::AttackMagicPotency),
+        ("Magie curative", Attribute::HealingMagicPotency),
+        ("Célérité", Attribute::SpellSpeed),
+        ("Ténacité", Attribute::Tenacity),
+        ("Piété", Attribute::Piety),
+        ("Habileté", Attribute::Craftsmanship),
+        ("Contrôle", Attribute::Control),
+        ("Collecte", Attribute::Gathering),
+        ("Discernement", Attribute::Perception),
+    ],
+    soul_crystals: &[],
+    weapons: &[],
+    jobs: &[
+        ("Paladin", Job::Paladin),
+        ("Gladiateur", Job::Paladin),
+        ("Guerrier", Job::Warrior),
+        ("Maraudeur", Job::Warrior),
+        ("Chevalier noir", Job::DarkKnight),
+        ("Pistosabreur", Job::Gunbreaker),
+        ("Mage blanc", Job::WhiteMage),
+        ("Élémentaliste", Job::WhiteMage),
+        ("Érudit", Job::Scholar),
+        ("Astromancien", Job::Astrologian),
+        ("Sage", Job::Sage),
+        ("Moine", Job::Monk),
+        ("Pugiliste", Job::Monk),
+        ("Chevalier dragon", Job::Dragoon),
+        ("Maître d'hast", Job::Dragoon),
+        ("Ninja", Job::Ninja),
+        ("Surineur", Job::Ninja),
+        ("Samouraï", Job::Samurai),
+        ("Faucheur", Job::Reaper),
+        ("Rôdeur vipère", Job::Viper),
+        ("Barde", Job::Bard),
+        ("Archer", Job::Bard),
+        ("Machiniste", Job::Machinist),
+        ("Danseur", Job::Dancer),
+        ("Mage noir", Job::BlackMage),
+        ("Occultiste", Job::BlackMage),
+        ("Invocateur", Job::Summoner),
+        ("Arcaniste", Job::Summoner),
+        ("Mage rouge", Job::RedMage),
+        ("Pictomancien", Job::Pictomancer),
+        ("Mage bleu", Job::BlueMage),
+        ("Menuisier", Job::Carpenter),
+        ("Armurier", Job::Armorer),
+        ("Forgeron", Job::Blacksmith),
+        ("Orfèvre", Job::Goldsmith),
+        ("Tanneur", Job::Leatherworker),
+        ("Couturier", Job::Weaver),
+        ("Alchimiste", Job::Alchemist),
+        ("Cuisinier", Job::Culinarian),
+        ("Mineur", Job::Miner),
+        ("Botaniste", Job::Botanist),
+        ("Pêcheur", Job::Fisher),
+    ],
+    content: &[
+        ("Niveau élémentaire", Content::Eureka),
+        ("Rang de résistance", Content::Bozja),
+    ],
+    values: &[
+        // races
+        ("Élézen", "Elezen"),
+        ("Ao Ra", "Au Ra"),
+        ("Viéra", "Viera"),
+        // clans
+        ("Hyurois", "Midlander"),
+        ("Hyuroise", "Midlander"),
+        ("Hyurgoth", "Highlander"),
+        ("Sylvestre", "Wildwood"),
+        ("Crépusculaire", "Duskwight"),
+        ("Peuple des Plaines", "Plainsfolk"),
+        ("Peuple des Dunes", "Dunesfolk"),
+        ("Tribu du Soleil", "Seeker of the Sun"),
+        ("Tribu de la Lune", "Keeper of the Moon"),
+        ("Clan de la Mer", "Sea Wolf"),
+        ("Clan du Feu", "Hellsguard"),
+        ("Hélion", "Helions"),
+        ("Lost", "The Lost"),
+        // guardians
+        ("Halone, la Conquérante", "Halone, the Fury"),
+        ("Menphina, la Bien-aimée", "Menphina, the Lover"),
+        ("Thaliak, l'Érudit", "Thaliak, the Scholar"),
+        ("Nymeia, la Fileuse", "Nymeia, the Spinner"),
+        ("Llymlaen, la Navigatrice", "Llymlaen, the Navigator"),
+        ("Oschon, le Vagabond", "Oschon, the Wanderer"),
+        ("Byregot, le Bâtisseur", "Byregot, the Builder"),
+        ("Rhalgr, le Destructeur", "Rhalgr, the Destroyer"),
+        ("Azeyma, la Gardienne", "Azeyma, the Warden"),
+        ("Nald'thal, les Marchands", "Nald'thal, the Traders"),
+        ("Nophica, la Mère", "Nophica, the Matron"),
+        ("Althyk, le Gardien", "Althyk, the Keeper"),
+        // grand companies
+        ("Le Maelstrom", "Maelstrom"),
+        ("L'ordre des Deux Vipères", "Order of the Twin Adder"),
+        ("Les Immortels", "Immortal Flames"),
+    ],
+    moons: ("astral", "ombral"),
+    month_first: false,
+};
+
+const GERMAN: Locale = Locale {
+    headings: &[
+        ("Volk / Stamm / Geschlecht", Heading::RaceClanGender),
+        ("Schutzgott", Heading::Guardian),
+        ("Stadtstaat", Heading::CityState),
+        ("Staatliche Gesellschaft", Heading::GrandCompany),
+        ("Große Gesellschaft", Heading::GrandCompany),
+    ],
+    hp: "p.character__param__text__hp--de + span",
+    mp: "p.character__param__text__mp--de + span",
+    cp: "p.character__param__text__cp--de + span",
+    gp: "p.character__param__text__gp--de + span",
+    attributes: &[
+        ("Stärke", Attribute::Strength),
+        ("Geschicklichkeit", Attribute::Dexterity),
+        ("Konstitution", Attribute::Vitality),
+        ("Intelligenz", Attribute::Intelligence),
+        ("Willenskraft", Attribute::Mind),
+        ("Kritischer Treffer", Attribute::CriticalHitRate),
+        ("Entschlossenheit", Attribute::Determination),
+        ("Direkter Treffer", Attribute::DirectHitRate),
+        ("Verteidigung", Attribute::Defense),
+        ("Magieabwehr", Attribute::MagicDefense),
+        ("Angriffskraft", Attribute::AttackPower),
+        ("Schnelligkeit", Attribute::SkillSpeed),
+        ("Offensivmagie", Attribute::AttackMagicPotency),
+        ("Heilmagie", Attribute::HealingMagicPotency),
+        ("Zaubertempo", Attribute::SpellSpeed),
+        ("Unbeugsamkeit", Attribute::Tenacity),
+        ("Frömmigkeit", Attribute::Piety),
+        ("Kunstfertigkeit", Attribute::Craftsmanship),
+        ("Kontrolle", Attribute::Control),
+        ("Sammelgeschick", Attribute::Gathering),
+        ("Wahrnehmung", Attribute::Perception),
+    ],
+    soul_crystals: &[],
+    weapons: &[],
+    jobs: &[
+        ("Paladin", Job::Paladin),
+        ("Gladiator", Job::Paladin),
+        ("Krieger", Job::Warrior),
+        ("Marodeur", Job::Warrior),
+        ("Dunkelritter", Job::DarkKnight),
+        ("Revolverklinge", Job::Gunbreaker),
+        ("Weißmagier", Job::WhiteMage),
+        ("Druide", Job::WhiteMage),
+        ("Gelehrter", Job::Scholar),
+        ("Astrologe", Job::Astrologian),
+        ("Weiser", Job::Sage),
+        ("Mönch", Job::Monk),
+        ("Faustkämpfer", Job::Monk),
+        ("Dragoon", Job::Dragoon),
+        ("Pikenier", Job::Dragoon),
+        ("Ninja", Job::Ninja),
+        ("Schurke", Job::Ninja),
+        ("Samurai", Job::Samurai),
+        ("Schnitter", Job::Reaper),
+        ("Viper", Job::Viper),
+        ("Barde", Job::Bard),
+        ("Waldläufer", Job::Bard),
+        ("Maschinist", Job::Machinist),
+        ("Tänzer", Job::Dancer),
+        ("Schwarzmagier", Job::BlackMage),
+        ("Thaumaturg", Job::BlackMage),
+        ("Beschwörer", Job::Summoner),
+        ("Hermetiker", Job::Summoner),
+        ("Rotmagier", Job::RedMage),
+        ("Piktomant", Job::Pictomancer),
+        ("Blaumagier", Job::BlueMage),
+        ("Zimmerer", Job::Carpenter),
+        ("Plattner", Job::Armorer),
+        ("Grobschmied", Job::Blacksmith),
+        ("Goldschmied", Job::Goldsmith),
+        ("Gerber", Job::Leatherworker),
+        ("Weber", Job::Weaver),
+        ("Alchemist", Job::Alchemist),
+        ("Gourmet", Job::Culinarian),
+        ("Minenarbeiter", Job::Miner),
+        ("Gärtner", Job::Botanist),
+        ("Fischer", Job::Fisher),
+    ],
+    content: &[
+        ("Elementarstufe", Content::Eureka),
+        ("Widerstandsrang", Content::Bozja),
+    ],
+    values: &[
+        // races
+        ("Hyuran", "Hyur"),
+        // clans
+        ("Wiesländer", "Midlander"),
+        ("Hochländer", "Highlander"),
+        ("Erlschatten", "Wildwood"),
+        ("Dunkelalb", "Duskwight"),
+        ("Halmling", "Plainsfolk"),
+        ("Sandling", "Dunesfolk"),
+        ("Goldtatze", "Seeker of the Sun"),
+        ("Mondstreuner", "Keeper of the Moon"),
+        ("Seewolf", "Sea Wolf"),
+        ("Lohengarde", "Hellsguard"),
+        ("Helion", "Helions"),
+        ("Verlorener", "The Lost"),
+        // guardians
+        ("Halone - Die Furie", "Halone, the Fury"),
+        ("Menphina - Die Liebende", "Menphina, the Lover"),
+        ("Thaliak - Der Forscher", "Thaliak, the Scholar"),
+        ("Nymeia - Die Spinnerin", "Nymeia, the Spinner"),
+        ("Llymlaen - Die Navigatorin", "Llymlaen, the Navigator"),
+        ("Oschon - Der Wanderer", "Oschon, the Wanderer"),
+        ("Byregot - Der Erbauer", "Byregot, the Builder"),
+        ("Rhalgr - Der Zerstörer", "Rhalgr, the Destroyer"),
+        ("Azeyma - Die Hüterin", "Azeyma, the Warden"),
+        ("Nald'thal - Die Händler", "Nald'thal, the Traders"),
+        ("Nophica - Die Mutter", "Nophica, the Matron"),
+        ("Althyk - Der Hüter", "Althyk, the Keeper"),
+        // grand companies
+        ("Mahlstrom", "Maelstrom"),
+        ("Bruderschaft der Morgenviper", "Order of the Twin Adder"),
+        ("Legion der Unsterblichen", "Immortal Flames"),
+    ],
+    moons: ("astral", "umbral"),
+    month_first: false,
+};
+
+const JAPANESE: Locale = Locale {
+    headings: &[
+        ("種族/部族/性別", Heading::RaceClanGender),
+        ("守護神", Heading::Guardian),
+        ("開始都市", Heading::CityState),
+        ("グランドカンパニー", Heading::GrandCompany),
+    ],
+    hp: "p.character__param__text__hp--ja + span",
+    mp: "p.character__param__text__mp--ja + span",
+    cp: "p.character__param__text__cp--ja + span",
+    gp: "p.character__param__text__gp--ja + span",
+    attributes: &[
+        ("STR", Attribute::Strength),
+        ("DEX", Attribute::Dexterity),
+        ("VIT", Attribute::Vitality),
+        ("INT", Attribute::Intelligence),
+        ("MND", Attribute::Mind),
+        ("クリティカル", Attribute::CriticalHitRate),
+        ("意思力", Attribute::Determination),
+        ("ダイレクトヒット", Attribute::DirectHitRate),
+        ("防御力", Attribute::Defense),
+        ("魔法防御力", Attribute::MagicDefense),
+        ("物理攻撃力", Attribute::AttackPower),
+        ("スキルスピード", Attribute::SkillSpeed),
+        ("攻撃魔法威力", Attribute::AttackMagicPotency),
+        ("回復魔法威力", Attribute::HealingMagicPotency),
+        ("スペルスピード", Attribute::SpellSpeed),
+        ("不屈", Attribute::Tenacity),
+        ("信仰", Attribute::Piety),
+        ("作業精度", Attribute::Craftsmanship),
+        ("加工精度", Attribute::Control),
+        ("獲得力", Attribute::Gathering),
+        ("識質力", Attribute::Perception),
+    ],
+    soul_crystals: &[],
+    weapons: &[],
+    jobs: &[
+        ("ナイト", Job::Paladin),
+        ("剣術士", Job::Paladin),
+        ("戦士", Job::Warrior),
+        ("斧術士", Job::Warrior),
+        ("暗黒騎士", Job::DarkKnight),
+        ("ガンブレイカー", Job::Gunbreaker),
+        ("白魔道士", Job::WhiteMage),
+        ("幻術士", Job::WhiteMage),
+        ("学者", Job::Scholar),
+        ("占星術師", Job::Astrologian),
+        ("賢者", Job::Sage),
+        ("モンク", Job::Monk),
+        ("格闘士", Job::Monk),
+        ("竜騎士", Job::Dragoon),
+        ("槍術士", Job::Dragoon),
+        ("忍者", Job::Ninja),
+        ("双剣士", Job::Ninja),
+        ("侍", Job::Samurai),
+        ("リーパー", Job::Reaper),
+        ("ヴァイパー", Job::Viper),
+        ("吟遊詩人", Job::Bard),
+        ("弓術士", Job::Bard),
+        ("機工士", Job::Machinist),
+        ("踊り子", Job::Dancer),
+        ("黒魔道士", Job::BlackMage),
+        ("呪術士", Job::BlackMage),
+        ("召喚士", Job::Summoner),
+        ("巴術士", Job::Summoner),
+        ("赤魔道士", Job::RedMage),
+        ("ピクトマンサー", Job::Pictomancer),
+        ("青魔道士", Job::BlueMage),
+        ("木工師", Job::Carpenter),
+        ("鍛冶師", Job::Blacksmith),
+        ("甲冑師", Job::Armorer),
+        ("彫金師", Job::Goldsmith),
+        ("革細工師", Job::Leatherworker),
+        ("裁縫師", Job::Weaver),
+        ("錬金術師", Job::Alchemist),
+        ("調理師", Job::Culinarian),
+        ("採掘師", Job::Miner),
+        ("園芸師", Job::Botanist),
+        ("漁師", Job::Fisher),
+    ],
+    content: &[
+        ("エレメンタルレベル", Content::Eureka),
+        ("レジスタンスランク", Content::Bozja),
+    ],
+    values: &[
+        // races
+        ("ヒューラン", "Hyur"),
+        ("エレゼン", "Elezen"),
+        ("ララフェル", "Lalafell"),
+        ("ミコッテ", "Miqo'te"),
+        ("ルガディン", "Roegadyn"),
+        ("アウラ", "Au Ra"),
+        ("ロスガル", "Hrothgar"),
+        ("ヴィエラ", "Viera"),
+        // clans
+        ("ミッドランダー", "Midlander"),
+        ("ハイランダー", "Highlander"),
+        ("フォレスター", "Wildwood"),
+        ("シェーダー", "Duskwight"),
+        ("プレーンフォーク", "Plainsfolk"),
+        ("デューンフォーク", "Dunesfolk"),
+        ("サンシーカー", "Seeker of the Sun"),
+        ("ムーンキーパー", "Keeper of the Moon"),
+        ("ゼーヴォルフ", "Sea Wolf"),
+        ("ローエンガルデ", "Hellsguard"),
+        ("アウラ・レン", "Raen"),
+        ("アウラ・ゼラ", "Xaela"),
+        ("ヘリオン", "Helions"),
+        ("ロスト", "The Lost"),
+        ("ラヴァ・ヴィエラ", "Rava"),
+        ("ヴィナ・ヴィエラ", "Veena"),
+        // guardians
+        ("戦神 ハルオーネ", "Halone, the Fury"),
+        ("月神 メネフィナ", "Menphina, the Lover"),
+        ("知神 サリャク", "Thaliak, the Scholar"),
+        ("星神 ニメーヤ", "Nymeia, the Spinner"),
+        ("海神 リムレーン", "Llymlaen, the Navigator"),
+        ("放浪神 オシュオン", "Oschon, the Wanderer"),
+        ("工神 ビエルゴ", "Byregot, the Builder"),
+        ("破壊神 ラールガー", "Rhalgr, the Destroyer"),
+        ("太陽神 アーゼマ", "Azeyma, the Warden"),
+        ("商神 ナルザル", "Nald'thal, the Traders"),
+        ("地神 ノフィカ", "Nophica, the Matron"),
+        ("時神 アルジク", "Althyk, the Keeper"),
+        // city-states
+        ("リムサ・ロミンサ", "Limsa Lominsa"),
+        ("グリダニア", "Gridania"),
+        ("ウルダハ", "Ul'dah"),
+        // grand companies
+        ("黒渦団", "Maelstrom"),
+        ("双蛇党", "Order of the Twin Adder"),
+        ("不滅隊", "Immortal Flames"),
+        // grand company ranks
+        ("二等甲兵", "Storm Private Third Class"),
+        ("一等甲兵", "Storm Private Second Class"),
+        ("上等甲兵", "Storm Private First Class"),
+        ("甲兵長", "Storm Corporal"),
+        ("三等甲曹", "Storm Sergeant Third Class"),
+        ("二等甲曹", "Storm Sergeant Second Class"),
+        ("一等甲曹", "Storm Sergeant First Class"),
+        ("甲曹長", "Chief Storm Sergeant"),
+        ("少甲尉", "Second Storm Lieutenant"),
+        ("中甲尉", "First Storm Lieutenant"),
+        ("大甲尉", "Storm Captain"),
+        ("二等双蛇兵", "Serpent Private Third Class"),
+        ("一等双蛇兵", "Serpent Private Second Class"),
+        ("上等双蛇兵", "Serpent Private First Class"),
+        ("双蛇兵長", "Serpent Corporal"),
+        ("三等双蛇曹", "Serpent Sergeant Third Class"),
+        ("二等双蛇曹", "Serpent Sergeant Second Class"),
+        ("一等双蛇曹", "Serpent Sergeant First Class"),
+        ("双蛇曹長", "Chief Serpent Sergeant"),
+        ("少蛇尉", "Second Serpent Lieutenant"),
+        ("中蛇尉", "First Serpent Lieutenant"),
+        ("大蛇尉", "Serpent Captain"),
+        ("二等闘兵", "Flame Private Third Class"),
+        ("一等闘兵", "Flame Private Second Class"),
+        ("上等闘兵", "Flame Private First Class"),
+        ("闘兵長", "Flame Corporal"),
+        ("三等闘曹", "Flame Sergeant Third Class"),
+        ("二等闘曹", "Flame Sergeant Second Class"),
+        ("一等闘曹", "Flame Sergeant First Class"),
+        ("闘曹長", "Chief Flame Sergeant"),
+        ("少闘尉", "Second Flame Lieutenant"),
+        ("中闘尉", "First Flame Lieutenant"),
+        ("大闘尉", "Flame Captain"),
+    ],
+    moons: ("星", "霊"),
+    month_first: true,
+};
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn translate_to_english() {
+        let german = Language::from(Region::De).locale();
+        assert_eq!(
+            german.heading("Volk / Stamm /\n Geschlecht"),
+            Some(Heading::RaceClanGender)
+        );
+        assert_eq!(german.heading("Race/Clan/Gender"), None);
+        assert_eq!(german.job("Weißmagier"), Some(Job::WhiteMage));
+        assert_eq!(
+            german.english("Legion der Unsterblichen"),
+            "Immortal Flames"
+        );
+        // names which are the same in both languages are kept
+        assert_eq!(german.english("Lalafell"), "Lalafell");
+
+        let english = Language::from(Region::Eu).locale();
+        assert_eq!(english.heading("City-state"), Some(Heading::CityState));
+        assert_eq!(english.job("Conjurer"), Some(Job::WhiteMage));
+
+        let japanese = Language::from(Region::Jp).locale();
+        assert_eq!(japanese.job("巴術士"), Some(Job::Summoner));
+        assert_eq!(
+            japanese.english("破壊神 ラールガー"),
+            "Rhalgr, the Destroyer"
+        );
+        assert_eq!(japanese.english("中蛇尉"), "First Serpent Lieutenant");
+        assert_eq!(
+            japanese.nameday("霊5月24日"),
+            "24th Sun of the 5th Umbral Moon"
+        );
+        assert_eq!(
+            german.nameday("3. Sonne im 1. Astralmond"),
+            "3rd Sun of the 1st Astral Moon"
+        );
+        assert_eq!(
+            english.nameday("12th Sun of the 2nd Umbral Moon"),
+            "12th Sun of the 2nd Umbral Moon"
+        );
+
+        // every job can be parsed in every language
+        for language in [Language::French, Language::German, Language::Japanese] {
+            for job in Job::iter() {
+                assert!(
+                    language
+                        .locale()
+                        .jobs
+                        .iter()
+                        .any(|(_, known)| *known == job),
+                    "{job} missing from {language:?}"
+                );
+            }
+        }
+    }
+    #[test]
+    fn active_job() {
+        let english = Language::English.locale();
+        let german = Language::German.locale();
+        let japanese = Language::Japanese.locale();
+        assert_eq!(
+            english.active_job(Some("Soul of the Warrior"), Some("Marauder's Arm")),
+            Some(Job::Warrior)
+        );
+        assert_eq!(
+            english.active_job(None, Some("Two-handed Thaumaturge\u{2019}s Arm")),
+            Some(Job::BlackMage)
+        );
+        assert_eq!(english.active_job(None, None), None);
+        let french = Language::French.locale();
+        assert_eq!(
+            french.active_job(Some("Cristal de l'âme de chevalier noir"), None),
+            Some(Job::DarkKnight)
+        );
+        assert_eq!(
+            french.active_job(None, Some("Arme de maraudeur")),
+            Some(Job::Warrior)
+        );
+        assert_eq!(
+            german.active_job(Some("Seelenkristall des Weißmagiers"), None),
+            Some(Job::WhiteMage)
+        );
+        assert_eq!(
+            japanese.active_job(Some("ソウルクリスタル：ナイト"), Some("剣術士武器")),
+            Some(Job::Paladin)
+        );
+        assert_eq!(german.active_job(Some("Gobbue-Puppe"), None), None);
+    }
+}
@@ -1,13 +1,46 @@
+mod attributes;
+mod bulk;
+mod collection;
+mod config;
+mod content;
+mod daemon;
 mod db;
+mod diff;
+mod error;
+mod experience;
+mod free_company;
+mod gear;
 mod job;
+mod json;
+mod locale;
 mod profile;
+mod region;
+mod render;
+mod search;
+mod server;
+mod site;
+mod stats;
 
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
-use db::TrackerDatabase;
+use attributes::Attribute;
+use bulk::RateLimit;
+use collection::{Collectable, Collection};
+use config::{parse_time, Character, Config, Interval};
+use content::{Content, ContentSnapshot};
+use db::{ExperienceRecord, SnapshotOutcome, TrackerDatabase};
+use diff::SnapshotDiff;
+use error::Error;
+use free_company::FreeCompany;
+use job::{Job, JobSnapshot};
 use profile::Profile;
+use region::Region;
+use search::SearchResult;
 
-use clap::{Parser, Subcommand};
+use chrono::{DateTime, TimeDelta, Utc};
+use clap::{Parser, Subcommand, ValueEnum};
+use strum::IntoEnumIterator;
 use tracing::{event, info_span, instrument, Level};
 
 #[derive(Parser)]
@@ -19,40 +52,1112 @@ struct Cli {
 
 #[derive(Subcommand)]
 enum Command {
-    /// Download/update files for running tests.
+    /// Download a player's profile from lodestone and record it in the
+    /// database, unless it is unchanged since the last snapshot.
+    ///
+    /// With `--all`, snapshots every character in the tracker config which is
+    /// due for polling, into the database named by the config.
     Snapshot {
+        #[arg(required_unless_present = "all")]
+        user_id: Option<u64>,
+        /// Database to record the snapshot in. With `--all`, the database
+        /// named by the config is used instead.
+        #[arg(default_value = "./ffxiv-tracker.sqlite", conflicts_with = "all")]
+        database_path: PathBuf,
+        /// Lodestone site to fetch from. With `--all`, each character's site
+        /// in the config is used instead.
+        #[arg(long, default_value_t = Region::Na, conflicts_with = "all")]
+        region: Region,
+        /// Snapshot every character in the tracker config.
+        #[arg(long, conflicts_with = "user_id")]
+        all: bool,
+        #[arg(long, default_value = "./tracker.kdl")]
+        config: PathBuf,
+    },
+    /// Print the experience history of a player, per job.
+    History {
+        user_id: u64,
+        /// Only show this job (e.g. "Dark Knight").
+        #[arg(long)]
+        job: Option<Job>,
+        /// Only show snapshots captured at or after this time (RFC 3339, or a date).
+        #[arg(long, value_parser = parse_time)]
+        since: Option<DateTime<Utc>>,
+        /// Only show snapshots captured at or before this time (RFC 3339, or a date).
+        #[arg(long, value_parser = parse_time)]
+        until: Option<DateTime<Utc>>,
+        #[arg(long, value_enum, default_value_t = Format::Table)]
+        format: Format,
+        #[arg(default_value = "./ffxiv-tracker.sqlite")]
+        database_path: PathBuf,
+    },
+    /// Print what changed between two snapshots of a player.
+    ///
+    /// By default, compares the latest snapshot with the one before it.
+    Diff {
+        user_id: u64,
+        /// Snapshot ID to compare from.
+        #[arg(long, conflicts_with = "since")]
+        from: Option<i64>,
+        /// Snapshot ID to compare to, instead of the latest snapshot.
+        #[arg(long)]
+        to: Option<i64>,
+        /// Compare from the latest snapshot captured at or before this time.
+        #[arg(long, value_parser = parse_time)]
+        since: Option<DateTime<Utc>>,
+        #[arg(default_value = "./ffxiv-tracker.sqlite")]
+        database_path: PathBuf,
+    },
+    /// Print a player's average item level over time, and their latest gear.
+    Gear {
+        user_id: u64,
+        #[arg(default_value = "./ffxiv-tracker.sqlite")]
+        database_path: PathBuf,
+    },
+    /// Print a player's attributes over time.
+    Attributes {
         user_id: u64,
         #[arg(default_value = "./ffxiv-tracker.sqlite")]
         database_path: PathBuf,
     },
+    /// Print leveling rates per job, and when each job will reach the level cap.
+    Eta {
+        user_id: u64,
+        /// Measure leveling rates over this many days.
+        #[arg(long, default_value_t = 7)]
+        days: u32,
+        #[arg(default_value = "./ffxiv-tracker.sqlite")]
+        database_path: PathBuf,
+    },
+    /// Search lodestone for characters by name, optionally on one world.
+    Search {
+        name: String,
+        world: Option<String>,
+        /// Lodestone site to search.
+        #[arg(long, default_value_t = Region::Na)]
+        region: Region,
+    },
+    /// Manage the characters in the tracker config.
+    Track {
+        #[command(subcommand)]
+        command: TrackCommand,
+    },
+    /// Track free company rosters.
+    #[command(alias = "fc")]
+    FreeCompany {
+        #[command(subcommand)]
+        command: FreeCompanyCommand,
+    },
+    /// Track achievements, mounts and minions.
+    Collection {
+        #[command(subcommand)]
+        command: CollectionCommand,
+    },
+    /// Write an HTML page charting level and experience per job over time.
+    Render {
+        /// Database to chart.
+        #[arg(long = "database", default_value = "./ffxiv-tracker.sqlite")]
+        database_path: PathBuf,
+        /// Page to write.
+        #[arg(long, default_value = "./ffxiv-tracker.html")]
+        output: PathBuf,
+        /// Character to chart, instead of every character in the database.
+        /// May be given more than once.
+        #[arg(long = "character")]
+        user_ids: Vec<u64>,
+    },
+    /// Write a standalone chart page, which loads a tracker database in the
+    /// browser and can be hosted on any static file host.
+    Bundle {
+        /// Directory to write the page to, as `index.html`.
+        out_dir: PathBuf,
+        /// Database to copy beside the page, where it is loaded automatically.
+        #[arg(long = "database", default_value = "./ffxiv-tracker.sqlite")]
+        database_path: PathBuf,
+        /// Leave the database out, so that visitors choose one to load.
+        #[arg(long, conflicts_with = "database_path")]
+        no_database: bool,
+    },
+    /// Generate a static site with an index of every character's levels, a
+    /// page per character and a free company leaderboard.
+    ///
+    /// Only pages of characters with new snapshots are regenerated.
+    Site {
+        /// Directory to write the site to.
+        out_dir: PathBuf,
+        /// Database to generate the site from.
+        #[arg(long = "database", default_value = "./ffxiv-tracker.sqlite")]
+        database_path: PathBuf,
+        /// Regenerate every page, even if unchanged.
+        #[arg(long)]
+        force: bool,
+    },
+    /// Serve the database as a JSON API over HTTP, until interrupted.
+    ///
+    /// Characters in the tracker config can be snapshot on request with
+    /// `POST /characters/{id}/snapshot`.
+    Serve {
+        #[arg(default_value = "./ffxiv-tracker.sqlite")]
+        database_path: PathBuf,
+        /// Address to listen on.
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        address: String,
+        #[arg(long, default_value = "./tracker.kdl")]
+        config: PathBuf,
+    },
+    /// Keep polling every character in the tracker config until interrupted.
+    Daemon {
+        #[arg(long, default_value = "./tracker.kdl")]
+        config: PathBuf,
+        /// Polling interval for characters without one in the config.
+        #[arg(long, default_value = "1h")]
+        interval: Interval,
+    },
 }
 
-fn main() -> Result<(), String> {
+#[derive(Subcommand)]
+enum TrackCommand {
+    /// Look up a character by name and world, and add them to the tracker
+    /// config.
+    Add {
+        name: String,
+        world: String,
+        /// Lodestone site to search, and later fetch the character from.
+        #[arg(long, default_value_t = Region::Na)]
+        region: Region,
+        /// Name to refer to the character by, instead of their character name.
+        #[arg(long)]
+        nickname: Option<String>,
+        /// Minimum time between snapshots of this character.
+        #[arg(long)]
+        interval: Option<Interval>,
+        #[arg(long, default_value = "./tracker.kdl")]
+        config: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum FreeCompanyCommand {
+    /// Snapshot a free company's roster, and print who joined or left since
+    /// the previous snapshot.
+    Snapshot {
+        free_company_id: u64,
+        #[arg(default_value = "./ffxiv-tracker.sqlite")]
+        database_path: PathBuf,
+        /// Lodestone site to fetch from.
+        #[arg(long, default_value_t = Region::Na)]
+        region: Region,
+    },
+    /// Snapshot a free company's roster, then the profile of every member.
+    Track {
+        free_company_id: u64,
+        #[arg(default_value = "./ffxiv-tracker.sqlite")]
+        database_path: PathBuf,
+        /// Lodestone site to fetch from.
+        #[arg(long, default_value_t = Region::Na)]
+        region: Region,
+        /// Most profiles to download at once.
+        #[arg(long, default_value_t = 2)]
+        concurrency: usize,
+        /// Least time between starting one profile download and the next.
+        #[arg(long, default_value = "2s")]
+        delay: Interval,
+    },
+    /// Print who joined and left a free company over time.
+    Roster {
+        free_company_id: u64,
+        #[arg(default_value = "./ffxiv-tracker.sqlite")]
+        database_path: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum CollectionCommand {
+    /// Snapshot a player's achievements, mounts and minions, and print what
+    /// they obtained since the previous snapshot.
+    Snapshot {
+        user_id: u64,
+        #[arg(default_value = "./ffxiv-tracker.sqlite")]
+        database_path: PathBuf,
+        /// Lodestone site to fetch from.
+        #[arg(long, default_value_t = Region::Na)]
+        region: Region,
+    },
+    /// Print a player's achievement points over time, and their achievements.
+    Achievements {
+        user_id: u64,
+        #[arg(default_value = "./ffxiv-tracker.sqlite")]
+        database_path: PathBuf,
+    },
+    /// Print a player's mounts, and when each was first seen.
+    Mounts {
+        user_id: u64,
+        #[arg(default_value = "./ffxiv-tracker.sqlite")]
+        database_path: PathBuf,
+    },
+    /// Print a player's minions, and when each was first seen.
+    Minions {
+        user_id: u64,
+        #[arg(default_value = "./ffxiv-tracker.sqlite")]
+        database_path: PathBuf,
+    },
+}
+
+/// Output format for reports.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Format {
+    Table,
+    Json,
+    Csv,
+}
+
+fn main() -> Result<(), Error> {
     let cli = Cli::parse();
     tracing_subscriber::fmt::init();
 
     match cli.command {
         Command::Snapshot {
+            user_id: Some(user_id),
+            region,
+            database_path,
+            ..
+        } => snapshot(user_id, region, database_path),
+        Command::Snapshot { config, .. } => snapshot_all(config),
+        Command::History {
+            user_id,
+            job,
+            since,
+            until,
+            format,
+            database_path,
+        } => history(user_id, job, since, until, format, database_path),
+        Command::Diff {
+            user_id,
+            from,
+            to,
+            since,
+            database_path,
+        } => {
+            let database = TrackerDatabase::open(&database_path)?;
+            let diff = diff::diff(&database, user_id, from, to, since)?;
+            print_diff(&diff);
+            Ok(())
+        }
+        Command::Gear {
             user_id,
             database_path,
-        } => snapshot(user_id, database_path),
+        } => gear(user_id, database_path),
+        Command::Attributes {
+            user_id,
+            database_path,
+        } => attributes(user_id, database_path),
+        Command::Eta {
+            user_id,
+            days,
+            database_path,
+        } => eta(user_id, days, database_path),
+        Command::Search {
+            name,
+            world,
+            region,
+        } => {
+            let results = search::search(&name, world.as_deref(), region)?;
+            print_search_results(&results);
+            Ok(())
+        }
+        Command::Track {
+            command:
+                TrackCommand::Add {
+                    name,
+                    world,
+                    region,
+                    nickname,
+                    interval,
+                    config,
+                },
+        } => track_add(&name, &world, region, nickname, interval, config),
+        Command::FreeCompany {
+            command:
+                FreeCompanyCommand::Snapshot {
+                    free_company_id,
+                    database_path,
+                    region,
+                },
+        } => snapshot_free_company(free_company_id, region, database_path),
+        Command::FreeCompany {
+            command:
+                FreeCompanyCommand::Track {
+                    free_company_id,
+                    database_path,
+                    region,
+                    concurrency,
+                    delay: Interval(delay),
+                },
+        } => {
+            let limit = RateLimit {
+                concurrency,
+                delay: delay.to_std().unwrap_or_default(),
+            };
+            track_free_company(free_company_id, region, limit, database_path)
+        }
+        Command::FreeCompany {
+            command:
+                FreeCompanyCommand::Roster {
+                    free_company_id,
+                    database_path,
+                },
+        } => roster(free_company_id, database_path),
+        Command::Collection {
+            command:
+                CollectionCommand::Snapshot {
+                    user_id,
+                    database_path,
+                    region,
+                },
+        } => snapshot_collection(user_id, region, database_path),
+        Command::Collection {
+            command:
+                CollectionCommand::Achievements {
+                    user_id,
+                    database_path,
+                },
+        } => achievements(user_id, database_path),
+        Command::Collection {
+            command:
+                CollectionCommand::Mounts {
+                    user_id,
+                    database_path,
+                },
+        } => collectables(user_id, Collectable::Mount, database_path),
+        Command::Collection {
+            command:
+                CollectionCommand::Minions {
+                    user_id,
+                    database_path,
+                },
+        } => collectables(user_id, Collectable::Minion, database_path),
+        Command::Render {
+            database_path,
+            output,
+            user_ids,
+        } => render(user_ids, output, database_path),
+        Command::Bundle {
+            out_dir,
+            database_path,
+            no_database,
+        } => bundle(out_dir, (!no_database).then_some(database_path)),
+        Command::Site {
+            out_dir,
+            database_path,
+            force,
+        } => site(out_dir, database_path, force),
+        Command::Serve {
+            database_path,
+            address,
+            config,
+        } => serve(&address, config, database_path),
+        Command::Daemon {
+            config,
+            interval: Interval(interval),
+        } => daemon::run(&Config::load(&config)?, interval),
     }
 }
 
 #[instrument]
-fn snapshot(user_id: u64, database_path: PathBuf) -> Result<(), String> {
-    let database = info_span!("db").in_scope(|| {
-        let db = TrackerDatabase {
-            path: database_path,
-        };
+fn snapshot(user_id: u64, region: Region, database_path: PathBuf) -> Result<(), Error> {
+    let mut database = info_span!("db").in_scope(|| {
         event!(Level::INFO, "initializing database");
-        db.init()?;
-        Ok::<TrackerDatabase, String>(db)
+        TrackerDatabase::open(&database_path)
+    })?;
+    snapshot_player(&mut database, user_id, region)
+}
+
+#[instrument]
+fn snapshot_all(config_path: PathBuf) -> Result<(), Error> {
+    let config = Config::load(&config_path)?;
+    let mut database = info_span!("db").in_scope(|| {
+        event!(Level::INFO, "initializing database");
+        TrackerDatabase::open(&config.database)
+    })?;
+
+    let now = Utc::now();
+    let mut failures: Vec<(String, Error)> = vec![];
+    for character in &config.characters {
+        let span = info_span!(
+            "character",
+            id = character.id,
+            nickname = character.nickname,
+            region = %character.region
+        );
+        let _guard = span.enter();
+
+        if let Some(Interval(interval)) = character.interval {
+            let last_observed = match database.last_observed(character.id) {
+                Ok(last_observed) => last_observed,
+                Err(e) => {
+                    event!(Level::ERROR, "failed to check last observation: {e}");
+                    failures.push((character.label(), e));
+                    continue;
+                }
+            };
+            if let Some(last_observed) = last_observed {
+                if now - last_observed < interval {
+                    event!(Level::INFO, "skipping, last observed at {last_observed}");
+                    continue;
+                }
+            }
+        }
+        if let Err(e) = snapshot_player(&mut database, character.id, character.region) {
+            event!(Level::ERROR, "snapshot failed: {e}");
+            failures.push((character.label(), e));
+        }
+    }
+
+    if failures.is_empty() {
+        return Ok(());
+    }
+    println!(
+        "{} of {} snapshots failed:",
+        failures.len(),
+        config.characters.len()
+    );
+    for (label, error) in &failures {
+        println!("  {label}: {error}");
+    }
+    Err(Error::Batch {
+        failed: failures.len(),
+        total: config.characters.len(),
+    })
+}
+
+fn print_search_results(results: &[SearchResult]) {
+    if results.is_empty() {
+        println!("no characters found");
+        return;
+    }
+    println!("{:>10} {:<25} {:<25} free company", "id", "name", "world");
+    for result in results {
+        println!(
+            "{:>10} {:<25} {:<25} {}",
+            result.id,
+            result.name,
+            result.world,
+            result.free_company.as_deref().unwrap_or("-")
+        );
+    }
+}
+
+#[instrument]
+fn track_add(
+    name: &str,
+    world: &str,
+    region: Region,
+    nickname: Option<String>,
+    interval: Option<Interval>,
+    config_path: PathBuf,
+) -> Result<(), Error> {
+    let found = search::resolve(name, world, region)?;
+    event!(Level::INFO, "portrait: {}", found.portrait);
+    let character = Character {
+        id: found.id,
+        nickname: Some(nickname.unwrap_or(found.name)),
+        region,
+        interval,
+    };
+    Config::add_character(&config_path, &character)?;
+    println!(
+        "tracking {} ({}) in {}",
+        character.label(),
+        found.world,
+        config_path.display()
+    );
+    Ok(())
+}
+
+#[instrument]
+fn snapshot_free_company(
+    free_company_id: u64,
+    region: Region,
+    database_path: PathBuf,
+) -> Result<(), Error> {
+    let mut database = TrackerDatabase::open(&database_path)?;
+    let previous = database.rosters(free_company_id)?.pop();
+    let free_company = info_span!("fetch").in_scope(|| {
+        event!(Level::INFO, "downloading free company");
+        FreeCompany::get(free_company_id, region)
+    })?;
+    let roster_id = database.snapshot_free_company(&free_company)?;
+    println!(
+        "created roster ID: {roster_id} ({} «{}», {} members)",
+        free_company.name,
+        free_company.tag,
+        free_company.members.len()
+    );
+
+    if let Some(previous) = previous {
+        let changes = free_company::roster_changes(&previous.members, &free_company.members);
+        for member in &changes.joined {
+            println!("  joined: {} ({})", member.name, member.user_id);
+        }
+        for member in &changes.left {
+            println!("  left: {} ({})", member.name, member.user_id);
+        }
+    }
+    Ok(())
+}
+
+#[instrument]
+fn track_free_company(
+    free_company_id: u64,
+    region: Region,
+    limit: RateLimit,
+    database_path: PathBuf,
+) -> Result<(), Error> {
+    let mut database = TrackerDatabase::open(&database_path)?;
+    let free_company = info_span!("fetch").in_scope(|| {
+        event!(Level::INFO, "downloading free company");
+        FreeCompany::get(free_company_id, region)
+    })?;
+    database.snapshot_free_company(&free_company)?;
+
+    let user_ids: Vec<u64> = free_company
+        .members
+        .iter()
+        .map(|member| member.user_id)
+        .collect();
+    event!(
+        Level::INFO,
+        "snapshotting {} members of {}",
+        user_ids.len(),
+        free_company.name
+    );
+    let failures = bulk::snapshot_characters(&mut database, &user_ids, region, limit);
+
+    if failures.is_empty() {
+        println!("snapshotted {} members", user_ids.len());
+        return Ok(());
+    }
+    println!("{} of {} snapshots failed:", failures.len(), user_ids.len());
+    for (user_id, error) in &failures {
+        let name = free_company
+            .members
+            .iter()
+            .find(|member| member.user_id == *user_id)
+            .map(|member| member.name.as_str())
+            .unwrap_or_default();
+        println!("  {name} ({user_id}): {error}");
+    }
+    Err(Error::Batch {
+        failed: failures.len(),
+        total: user_ids.len(),
+    })
+}
+
+#[instrument]
+fn roster(free_company_id: u64, database_path: PathBuf) -> Result<(), Error> {
+    let database = TrackerDatabase::open(&database_path)?;
+    let rosters = database.rosters(free_company_id)?;
+    let Some(first) = rosters.first() else {
+        Err(Error::NoSnapshot(format!(
+            "of free company {free_company_id}"
+        )))?
+    };
+    println!(
+        "{}: {} members",
+        first.captured_at.to_rfc3339(),
+        first.members.len()
+    );
+    for pair in rosters.windows(2) {
+        let [before, after] = pair else {
+            continue;
+        };
+        let changes = free_company::roster_changes(&before.members, &after.members);
+        if changes.joined.is_empty() && changes.left.is_empty() {
+            continue;
+        }
+        println!(
+            "{}: {} members",
+            after.captured_at.to_rfc3339(),
+            after.members.len()
+        );
+        for member in &changes.joined {
+            println!("  joined: {} ({})", member.name, member.user_id);
+        }
+        for member in &changes.left {
+            println!("  left: {} ({})", member.name, member.user_id);
+        }
+    }
+    Ok(())
+}
+
+#[instrument]
+fn render(user_ids: Vec<u64>, output: PathBuf, database_path: PathBuf) -> Result<(), Error> {
+    let database = TrackerDatabase::open(&database_path)?;
+    let user_ids = match user_ids.is_empty() {
+        true => database
+            .characters()?
+            .iter()
+            .map(|character| character.user_id)
+            .collect(),
+        false => user_ids,
+    };
+    let page = render::render(&database, &user_ids, Utc::now())?;
+    std::fs::write(&output, page).map_err(|source| Error::Io {
+        path: output.clone(),
+        source,
     })?;
+    println!(
+        "wrote {} characters to {}",
+        user_ids.len(),
+        output.display()
+    );
+    Ok(())
+}
+
+fn bundle(out_dir: PathBuf, database_path: Option<PathBuf>) -> Result<(), Error> {
+    let io_error = |path: &PathBuf| {
+        let path = path.clone();
+        move |source| Error::Io { path, source }
+    };
+    std::fs::create_dir_all(&out_dir).map_err(io_error(&out_dir))?;
+    let page = out_dir.join("index.html");
+    std::fs::write(&page, render::bundle()).map_err(io_error(&page))?;
+    println!("wrote {}", page.display());
+
+    if let Some(database_path) = database_path {
+        let database = TrackerDatabase::open(&database_path)?;
+        let copy = out_dir.join(render::BUNDLE_DATABASE);
+        if copy.exists() {
+            std::fs::remove_file(&copy).map_err(io_error(&copy))?;
+        }
+        database.export(&copy)?;
+        println!("wrote {}", copy.display());
+    }
+    Ok(())
+}
+
+#[instrument]
+fn site(out_dir: PathBuf, database_path: PathBuf, force: bool) -> Result<(), Error> {
+    let database = TrackerDatabase::open(&database_path)?;
+    let generated = site::generate(&database, &out_dir, Utc::now(), force)?;
+    println!(
+        "wrote {} character pages to {} ({} unchanged)",
+        generated.characters.len(),
+        out_dir.display(),
+        generated.unchanged
+    );
+    Ok(())
+}
+
+#[instrument]
+fn serve(address: &str, config: PathBuf, database_path: PathBuf) -> Result<(), Error> {
+    let database = TrackerDatabase::open(&database_path)?;
+    // the read endpoints work without a config, but nothing can be snapshot
+    let tracked = match config.exists() {
+        true => Config::load(&config)?
+            .characters
+            .iter()
+            .map(|character| (character.id, character.region))
+            .collect(),
+        false => vec![],
+    };
+    server::run(address, database, tracked)
+}
+
+#[instrument]
+fn snapshot_collection(user_id: u64, region: Region, database_path: PathBuf) -> Result<(), Error> {
+    let mut database = TrackerDatabase::open(&database_path)?;
+    let first = database.collection_history(user_id)?.is_empty();
+    let collection = info_span!("fetch").in_scope(|| {
+        event!(Level::INFO, "downloading collection");
+        Collection::get(user_id, region)
+    })?;
+    let update = database.snapshot_collection(&collection)?;
+    println!(
+        "created collection ID: {} ({} achievement points, {} mounts, {} minions)",
+        update.collection_id,
+        optional(collection.achievement_points, "private"),
+        collection.mounts.len(),
+        collection.minions.len()
+    );
+
+    // everything is new the first time, so only list what's new after that
+    if !first {
+        for achievement in &update.achievements {
+            println!(
+                "  achievement: {} ({} points)",
+                achievement.name, achievement.points
+            );
+        }
+        for (kind, name) in &update.collectables {
+            println!("  {}: {name}", kind.to_string().to_lowercase());
+        }
+    }
+    Ok(())
+}
+
+#[instrument]
+fn achievements(user_id: u64, database_path: PathBuf) -> Result<(), Error> {
+    let database = TrackerDatabase::open(&database_path)?;
+    let history = database.collection_history(user_id)?;
+    if history.is_empty() {
+        Err(Error::NoSnapshot(format!("of {user_id}'s collection")))?;
+    }
+
+    println!(
+        "{:<25} {:>7} {:>12}",
+        "captured at", "points", "achievements"
+    );
+    for record in &history {
+        println!(
+            "{:<25} {:>7} {:>12}",
+            record.captured_at.to_rfc3339(),
+            optional(record.achievement_points, "-"),
+            record.achievements
+        );
+    }
+
+    println!();
+    println!("{:<10} {:>6}  achievement", "earned", "points");
+    for stored in database.achievements(user_id)? {
+        let earned = match stored.achievement.earned_at {
+            Some(t) => t.format("%Y-%m-%d").to_string(),
+            None => format!("~{}", stored.first_seen.format("%Y-%m-%d")),
+        };
+        println!(
+            "{:<10} {:>6}  {}",
+            earned, stored.achievement.points, stored.achievement.name
+        );
+    }
+    Ok(())
+}
+
+#[instrument]
+fn collectables(user_id: u64, kind: Collectable, database_path: PathBuf) -> Result<(), Error> {
+    let database = TrackerDatabase::open(&database_path)?;
+    println!("{:<25} {}", "first seen", kind.to_string().to_lowercase());
+    for collectable in database.collectables(user_id, kind)? {
+        println!(
+            "{:<25} {}",
+            collectable.first_seen.to_rfc3339(),
+            collectable.name
+        );
+    }
+    Ok(())
+}
+
+fn snapshot_player(
+    database: &mut TrackerDatabase,
+    user_id: u64,
+    region: Region,
+) -> Result<(), Error> {
+    if let Some(previous) = database.snapshots(user_id, None, None)?.last() {
+        let snapshot_id = previous.snapshot_id;
+        match previous.captured_at {
+            Some(captured_at) => {
+                event!(
+                    Level::INFO,
+                    "previous snapshot {snapshot_id} at {captured_at}"
+                )
+            }
+            None => event!(
+                Level::INFO,
+                "previous snapshot {snapshot_id} at unknown time"
+            ),
+        }
+    }
     let profile = info_span!("fetch").in_scope(|| {
         event!(Level::INFO, "downloading profile");
-        Profile::get(user_id)
+        Profile::get(user_id, region)
     })?;
 
-    database.snapshot(profile)
+    match database.snapshot(profile)? {
+        SnapshotOutcome::Created(snapshot_id) => println!("created snapshot ID: {}", snapshot_id),
+        SnapshotOutcome::Unchanged(snapshot_id) => {
+            println!("unchanged since snapshot ID: {}", snapshot_id)
+        }
+    }
+    Ok(())
+}
+
+#[instrument]
+fn history(
+    user_id: u64,
+    job: Option<Job>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    format: Format,
+    database_path: PathBuf,
+) -> Result<(), Error> {
+    let database = TrackerDatabase::open(&database_path)?;
+    let history = database.history(user_id, job, since, until)?;
+    // special content is shown after the jobs, unless a single job was asked for
+    let content = match job {
+        Some(_) => BTreeMap::new(),
+        None => database.content_history(user_id, since, until)?,
+    };
+
+    match format {
+        Format::Table => print_history_table(&history, &content),
+        Format::Json => {
+            let json = serde_json::to_string_pretty(&json::history(&history, &content))
+                .expect("JSON values always serialize");
+            println!("{json}");
+        }
+        Format::Csv => {
+            println!("captured_at,job,level,current_xp,max_xp,total_xp");
+            for (job, records) in &history {
+                for record in records {
+                    println!(
+                        "{},{},{},{},{},{}",
+                        record
+                            .captured_at
+                            .map(|t| t.to_rfc3339())
+                            .unwrap_or_default(),
+                        job,
+                        optional(record.level, ""),
+                        optional(record.exp.map(|(current, _)| current), ""),
+                        optional(record.exp.map(|(_, max)| max), ""),
+                        optional(experience::total_exp(*job, record.level, record.exp), ""),
+                    );
+                }
+            }
+            for (name, records) in &content {
+                for record in records {
+                    println!(
+                        "{},{},{},{},{},",
+                        record
+                            .captured_at
+                            .map(|t| t.to_rfc3339())
+                            .unwrap_or_default(),
+                        name,
+                        optional(record.level, ""),
+                        optional(record.exp.map(|(current, _)| current), ""),
+                        optional(record.exp.map(|(_, max)| max), ""),
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_history_table(
+    history: &BTreeMap<Job, Vec<ExperienceRecord>>,
+    content: &BTreeMap<Content, Vec<ExperienceRecord>>,
+) {
+    for (job, records) in history {
+        print_records(&job.to_string(), records, |record| {
+            let snapshot = JobSnapshot {
+                job: *job,
+                level: record.level,
+                exp: record.exp,
+            };
+            snapshot.percent_to_cap()
+        });
+    }
+    for (name, records) in content {
+        print_records(&name.to_string(), records, |_| None);
+    }
+}
+
+/// Print one section of the history table, with progress towards the level
+/// cap where it is known.
+fn print_records(
+    title: &str,
+    records: &[ExperienceRecord],
+    percent_to_cap: impl Fn(&ExperienceRecord) -> Option<f64>,
+) {
+    println!("{title}");
+    println!(
+        "  {:<25} {:>5} {:>12} {:>12} {:>7}",
+        "captured at", "level", "current xp", "max xp", "to cap"
+    );
+    for record in records {
+        let captured_at = match record.captured_at {
+            Some(t) => t.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+            None => "unknown".to_string(),
+        };
+        let to_cap = match percent_to_cap(record) {
+            Some(percent) => format!("{percent:.1}%"),
+            None => "-".to_string(),
+        };
+        println!(
+            "  {:<25} {:>5} {:>12} {:>12} {:>7}",
+            captured_at,
+            optional(record.level, "-"),
+            optional(record.exp.map(|(current, _)| current), "-"),
+            optional(record.exp.map(|(_, max)| max), "-"),
+            to_cap,
+        );
+    }
+    println!();
+}
+
+/// Format an optional value, using `missing` in its place if absent.
+fn optional(value: Option<u64>, missing: &str) -> String {
+    value
+        .map(|v| v.to_string())
+        .unwrap_or_else(|| missing.to_string())
+}
+
+#[instrument]
+fn gear(user_id: u64, database_path: PathBuf) -> Result<(), Error> {
+    let database = TrackerDatabase::open(&database_path)?;
+    let history = database.equipment_history(user_id)?;
+    let Some(latest) = history.last() else {
+        Err(Error::NoSnapshot(format!("of {user_id} with gear")))?
+    };
+
+    println!("{:<25} {:>10}", "captured at", "item level");
+    for record in &history {
+        println!(
+            "{:<25} {:>10}",
+            record
+                .captured_at
+                .map_or_else(|| "unknown".to_string(), |t| t.to_rfc3339()),
+            optional(gear::average_item_level(&record.equipment), "-")
+        );
+    }
+
+    println!();
+    println!("{:<12} {:>5} {:<40} glamour", "slot", "ilvl", "item");
+    for item in &latest.equipment {
+        let mut name = item.name.clone();
+        if item.high_quality {
+            name += " (HQ)";
+        }
+        println!(
+            "{:<12} {:>5} {:<40} {}",
+            item.slot.to_string(),
+            optional(item.item_level, "-"),
+            name,
+            item.glamour.as_deref().unwrap_or("-")
+        );
+        for materia in &item.materia {
+            println!("{:>19}{materia}", "");
+        }
+    }
+    Ok(())
+}
+
+#[instrument]
+fn attributes(user_id: u64, database_path: PathBuf) -> Result<(), Error> {
+    let database = TrackerDatabase::open(&database_path)?;
+    let history = database.attribute_history(user_id)?;
+    if history.is_empty() {
+        Err(Error::NoSnapshot(format!("of {user_id} with attributes")))?;
+    }
+    for record in &history {
+        println!(
+            "{}",
+            record
+                .captured_at
+                .map(|t| t.to_rfc3339())
+                .unwrap_or("unknown time".to_string())
+        );
+        for attribute in Attribute::iter() {
+            if let Some(value) = record.attributes.get(attribute) {
+                println!("  {:<22} {value:>6}", attribute.to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_diff(diff: &SnapshotDiff) {
+    let describe = |(snapshot_id, captured_at): (i64, Option<DateTime<Utc>>)| match captured_at {
+        Some(t) => format!(
+            "snapshot {snapshot_id} ({})",
+            t.format("%Y-%m-%d %H:%M UTC")
+        ),
+        None => format!("snapshot {snapshot_id} (unknown time)"),
+    };
+    println!("{} -> {}", describe(diff.before), describe(diff.after));
+
+    if diff.fields.is_empty() && diff.jobs.is_empty() && diff.content.is_empty() {
+        println!("no changes");
+    }
+    for change in &diff.fields {
+        println!(
+            "{}: {} -> {}",
+            change.field,
+            change.before.as_deref().unwrap_or("-"),
+            change.after.as_deref().unwrap_or("-"),
+        );
+    }
+    for change in &diff.jobs {
+        let job = match change.played {
+            true => format!("{} (played)", change.job),
+            false => change.job.to_string(),
+        };
+        if change.unlocked() {
+            println!(
+                "{job}: unlocked, now level {}",
+                optional(change.after.level, "-")
+            );
+        } else if let Some(levels @ 1..) = change.levels_gained() {
+            println!(
+                "{job}: level {} -> {} (+{levels}, +{} exp)",
+                optional(change.before.level, "-"),
+                optional(change.after.level, "-"),
+                optional(change.exp_gained(), "?"),
+            );
+        } else if let Some(exp) = change.exp_gained() {
+            println!("{job}: +{exp} exp");
+        } else {
+            println!(
+                "{job}: level {} -> {}",
+                optional(change.before.level, "-"),
+                optional(change.after.level, "-"),
+            );
+        }
+    }
+    for change in &diff.content {
+        let exp = |snapshot: &ContentSnapshot| match snapshot.exp {
+            Some((current, max)) => format!("{current}/{max}"),
+            None => "-".to_string(),
+        };
+        println!(
+            "{}: level {} ({} exp) -> {} ({} exp)",
+            change.content,
+            optional(change.before.level, "-"),
+            exp(&change.before),
+            optional(change.after.level, "-"),
+            exp(&change.after),
+        );
+    }
+}
+
+#[instrument]
+fn eta(user_id: u64, days: u32, database_path: PathBuf) -> Result<(), Error> {
+    let database = TrackerDatabase::open(&database_path)?;
+    let now = Utc::now();
+    let rates = stats::rates(&database, user_id, TimeDelta::days(days.into()), now)?;
+
+    println!(
+        "{:<14} {:>5} {:>10} {:>12} {:>10} {:>12}",
+        "job", "level", "xp/hour", "xp/day", "lv/week", "cap eta"
+    );
+    let fixed = |value: Option<f64>, precision: usize| match value {
+        Some(value) => format!("{value:.precision$}"),
+        None => "-".to_string(),
+    };
+    for (job, rate) in &rates {
+        let eta = match (rate.exp_to_cap(), rate.eta(now)) {
+            (Some(0), _) => "capped".to_string(),
+            (_, Some(eta)) => eta.format("%Y-%m-%d").to_string(),
+            _ => "-".to_string(),
+        };
+        println!(
+            "{:<14} {:>5} {:>10} {:>12} {:>10} {:>12}",
+            job.to_string(),
+            optional(rate.current.level, "-"),
+            fixed(rate.exp_per_hour(), 0),
+            fixed(rate.exp_per_day(), 0),
+            fixed(rate.levels_per_week(), 2),
+            eta,
+        );
+    }
+    Ok(())
 }
@@ -1,3 +1,6 @@
+-- Initial schema. Tables are created with `IF NOT EXISTS` so that
+-- databases created before schema versioning are adopted as version 1.
+
 CREATE TABLE IF NOT EXISTS profile_snapshots (
     snapshot_id INTEGER PRIMARY KEY NOT NULL,
     user_id INTEGER NOT NULL,
@@ -0,0 +1,7 @@
+-- Record when each profile snapshot was captured, as a UTC timestamp.
+-- Snapshots taken before this migration have an unknown capture time, and are
+-- left as NULL.
+ALTER TABLE profile_snapshots ADD COLUMN captured_at TEXT;
+
+CREATE INDEX IF NOT EXISTS profile_snapshots_user_captured
+    ON profile_snapshots (user_id, captured_at);
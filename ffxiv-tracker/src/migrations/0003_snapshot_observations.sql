@@ -0,0 +1,12 @@
+-- Times at which a profile was observed unchanged since its latest snapshot.
+-- Instead of storing an identical snapshot, we record a heartbeat against the
+-- existing one.
+CREATE TABLE snapshot_observations (
+    observation_id INTEGER PRIMARY KEY NOT NULL,
+    snapshot_id INTEGER NOT NULL,
+    observed_at TEXT NOT NULL,
+    FOREIGN KEY (snapshot_id) REFERENCES profile_snapshots (snapshot_id)
+);
+
+CREATE INDEX snapshot_observations_snapshot
+    ON snapshot_observations (snapshot_id, observed_at);
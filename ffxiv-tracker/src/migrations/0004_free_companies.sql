@@ -0,0 +1,28 @@
+-- Free companies and their rosters over time.
+--
+-- Free company IDs are stored as text, since they don't fit in a signed 64-bit
+-- integer.
+ALTER TABLE profile_snapshots ADD COLUMN free_company_id TEXT;
+
+CREATE TABLE free_company_snapshots (
+    roster_id INTEGER PRIMARY KEY NOT NULL,
+    free_company_id TEXT NOT NULL,
+    captured_at TEXT NOT NULL,
+    name TEXT NOT NULL,
+    tag TEXT NOT NULL,
+    world TEXT NOT NULL,
+    grand_company TEXT,
+    slogan TEXT
+);
+
+CREATE INDEX free_company_snapshots_captured
+    ON free_company_snapshots (free_company_id, captured_at);
+
+CREATE TABLE free_company_members (
+    roster_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    rank TEXT NOT NULL,
+    PRIMARY KEY (roster_id, user_id),
+    FOREIGN KEY (roster_id) REFERENCES free_company_snapshots (roster_id)
+);
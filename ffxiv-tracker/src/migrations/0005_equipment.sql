@@ -0,0 +1,16 @@
+-- Equipped gear at the time of each profile snapshot. Snapshots taken before
+-- this migration have no gear recorded.
+CREATE TABLE equipment_snapshots (
+    id INTEGER PRIMARY KEY NOT NULL,
+    snapshot_id INTEGER NOT NULL,
+    slot TEXT NOT NULL,
+    item_name TEXT NOT NULL,
+    item_level INTEGER,
+    high_quality INTEGER NOT NULL,
+    -- JSON array of materia names, in socket order
+    materia TEXT NOT NULL,
+    glamour TEXT,
+    FOREIGN KEY (snapshot_id) REFERENCES profile_snapshots (snapshot_id)
+);
+
+CREATE INDEX equipment_snapshots_snapshot ON equipment_snapshots (snapshot_id);
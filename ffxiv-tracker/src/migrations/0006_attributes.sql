@@ -0,0 +1,11 @@
+-- Character attributes at the time of each profile snapshot, for the job
+-- equipped at the time. Only attributes shown for that job are stored.
+CREATE TABLE attribute_snapshots (
+    id INTEGER PRIMARY KEY NOT NULL,
+    snapshot_id INTEGER NOT NULL,
+    attribute TEXT NOT NULL,
+    value INTEGER NOT NULL,
+    FOREIGN KEY (snapshot_id) REFERENCES profile_snapshots (snapshot_id)
+);
+
+CREATE INDEX attribute_snapshots_snapshot ON attribute_snapshots (snapshot_id);
@@ -0,0 +1,4 @@
+-- The job a character had equipped, and its level, when each profile snapshot
+-- was captured. Unknown for snapshots taken before this migration.
+ALTER TABLE profile_snapshots ADD COLUMN active_job TEXT;
+ALTER TABLE profile_snapshots ADD COLUMN active_level INTEGER;
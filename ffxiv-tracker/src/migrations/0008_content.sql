@@ -0,0 +1,13 @@
+-- Progression in special content such as Eureka and Bozja at the time of
+-- each profile snapshot. Only content the player has unlocked is stored.
+CREATE TABLE content_snapshots (
+    id INTEGER PRIMARY KEY NOT NULL,
+    snapshot_id INTEGER NOT NULL,
+    content TEXT NOT NULL,
+    level INTEGER,
+    current_exp INTEGER,
+    max_exp INTEGER,
+    FOREIGN KEY (snapshot_id) REFERENCES profile_snapshots (snapshot_id)
+);
+
+CREATE INDEX content_snapshots_snapshot ON content_snapshots (snapshot_id);
@@ -0,0 +1,35 @@
+-- Achievements, mounts and minions. Each is stored once per character, with
+-- the collection snapshot it was first seen in, so that collectables without
+-- a date on lodestone still show roughly when they were obtained.
+CREATE TABLE collection_snapshots (
+    collection_id INTEGER PRIMARY KEY NOT NULL,
+    user_id INTEGER NOT NULL,
+    captured_at TEXT NOT NULL,
+    -- NULL when the character's achievements are private
+    achievement_points INTEGER,
+    achievements INTEGER NOT NULL,
+    mounts INTEGER NOT NULL,
+    minions INTEGER NOT NULL
+);
+
+CREATE INDEX collection_snapshots_captured ON collection_snapshots (user_id, captured_at);
+
+CREATE TABLE achievements (
+    user_id INTEGER NOT NULL,
+    achievement_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    points INTEGER NOT NULL,
+    earned_at TEXT,
+    first_seen INTEGER NOT NULL,
+    PRIMARY KEY (user_id, achievement_id),
+    FOREIGN KEY (first_seen) REFERENCES collection_snapshots (collection_id)
+);
+
+CREATE TABLE collectables (
+    user_id INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    name TEXT NOT NULL,
+    first_seen INTEGER NOT NULL,
+    PRIMARY KEY (user_id, kind, name),
+    FOREIGN KEY (first_seen) REFERENCES collection_snapshots (collection_id)
+);
@@ -1,13 +1,23 @@
+use crate::attributes::{self, Attributes};
+use crate::content::{self, PlayerContentSnapshot};
+use crate::error::Error;
+use crate::gear::{self, Equipment};
 use crate::job::{Job, JobSnapshot, PlayerJobSnapshot};
+use crate::locale::{Heading, Language};
+use crate::region::Region;
+use chrono::{DateTime, Utc};
 use scraper::{Element, ElementRef, Html, Selector};
 use std::collections::HashMap;
-use std::str::FromStr;
 
 /// A player profile snapshot, collected from their lodestone pages.
 #[derive(Debug)]
 pub struct Profile {
     pub user_id: u64,
+    /// When the profile was downloaded from lodestone.
+    pub captured_at: DateTime<Utc>,
     pub free_company: Option<String>,
+    /// Lodestone ID of the free company, from the link to its page.
+    pub free_company_id: Option<u64>,
     pub name: String,
     pub nameday: String,
     pub guardian: String,
@@ -20,108 +30,115 @@ pub struct Profile {
     pub grand_company_rank: Option<String>,
     pub hp: u64,
     pub mp: u64,
+    /// The job equipped when the profile was captured, if it could be
+    /// identified from the character's gear.
+    pub active_job: Option<Job>,
+    /// Level of the equipped job, from the profile header.
+    pub active_level: Option<u64>,
+    pub attributes: Attributes,
+    pub equipment: Vec<Equipment>,
     pub jobs: PlayerJobSnapshot,
+    /// Progression in special content such as Eureka and Bozja.
+    pub content: PlayerContentSnapshot,
 }
 
 impl Profile {
-    pub const BASE_URL: &'static str = "https://na.finalfantasyxiv.com/lodestone/character";
+    pub fn get(user_id: u64, region: Region) -> Result<Profile, Error> {
+        let base_url = region.character_url();
+        let profile_url = format!("{base_url}/{user_id}");
+        let profile_html = fetch(&profile_url, &[])?;
 
-    pub fn get(user_id: u64) -> Result<Profile, String> {
-        let profile_url = format!("{0}/{user_id}", Profile::BASE_URL);
-        let profile_html = ureq::get(profile_url.as_str())
-            .call()
-            .map_err(|e| e.to_string())?
-            .into_string()
-            .map_err(|e| e.to_string())?;
-        let profile_html = Html::parse_document(&profile_html);
+        let job_url = format!("{base_url}/{user_id}/class_job/");
+        let job_html = fetch(&job_url, &[])?;
 
-        let job_url = format!("{0}/{user_id}/class_job/", Profile::BASE_URL);
-        let job_html = ureq::get(job_url.as_str())
-            .call()
-            .map_err(|e| e.to_string())?
-            .into_string()
-            .map_err(|e| e.to_string())?;
-        let job_html = Html::parse_document(&job_html);
-
-        Self::parse(user_id, profile_html, job_html)
+        Self::parse(
+            user_id,
+            Utc::now(),
+            Language::from(region),
+            profile_html,
+            job_html,
+        )
     }
 
-    fn parse(user_id: u64, profile_html: Html, jobs_html: Html) -> Result<Profile, String> {
-        let select_free_company =
-            Selector::parse("div.character__freecompany__name h4").map_err(|e| e.to_string())?;
-        let select_name = Selector::parse("p.frame__chara__name").map_err(|e| e.to_string())?;
-        let select_nameday =
-            Selector::parse("p.character-block__birth").map_err(|e| e.to_string())?;
-        let select_profile_info =
-            Selector::parse("p.character-block__name").map_err(|e| e.to_string())?;
-        let select_server = Selector::parse("p.frame__chara__world").map_err(|e| e.to_string())?;
-        let select_hp = Selector::parse("p.character__param__text__hp--en-us + span")
-            .map_err(|e| e.to_string())?;
-        let select_mp = Selector::parse("p.character__param__text__mp--en-us + span")
-            .map_err(|e| e.to_string())?;
-        let free_company = match profile_html.select(&select_free_company).next() {
+    /// Parse a profile from pages in the given language. Localized values are
+    /// translated to English where possible.
+    fn parse(
+        user_id: u64,
+        captured_at: DateTime<Utc>,
+        language: Language,
+        profile_html: Html,
+        jobs_html: Html,
+    ) -> Result<Profile, Error> {
+        let locale = language.locale();
+        const FREE_COMPANY: &str = "div.character__freecompany__name h4";
+        const FREE_COMPANY_LINK: &str = "div.character__freecompany__name h4 a";
+        const NAME: &str = "p.frame__chara__name";
+        const NAMEDAY: &str = "p.character-block__birth";
+        const PROFILE_INFO: &str = "p.character-block__name";
+        const SERVER: &str = "p.frame__chara__world";
+
+        let profile = profile_html.root_element();
+        let free_company = match profile.select(&selector(FREE_COMPANY)?).next() {
             Some(element) => element.text().next().map(|txt| txt.to_string()),
             None => None,
         };
-        let name = profile_html
-            .select(&select_name)
-            .next()
-            .ok_or("couldn't find name")?
-            .text()
-            .collect::<String>();
-        let nameday = profile_html
-            .select(&select_nameday)
+        let free_company_id = profile
+            .select(&selector(FREE_COMPANY_LINK)?)
             .next()
-            .ok_or("couldn't find nameday")?
-            .text()
-            .collect::<String>();
-        let server = profile_html
-            .select(&select_server)
-            .next()
-            .ok_or("couldn't find server")?
-            .text()
-            .collect::<String>();
+            .and_then(|link| link.value().attr("href"))
+            .and_then(link_id);
+        let name = select_text(profile, NAME, "name")?;
+        let nameday = locale.nameday(&select_text(profile, NAMEDAY, "nameday")?);
+        let server = select_text(profile, SERVER, "server")?;
         // some of these elements are optional:
         // - free company
         // - grand company
         // - grand company rank
         // they all have the same CSS class, so we iterate over all matching
         // elements, and then see if we're missing anything important
-        let info: Vec<ElementRef> = profile_html.select(&select_profile_info).collect();
+        let info: Vec<ElementRef> = profile.select(&selector(PROFILE_INFO)?).collect();
         let mut info_blocks: HashMap<&str, String> = HashMap::new();
         for block in info {
+            let invalid = |field: &'static str| Error::InvalidElement {
+                field,
+                selector: PROFILE_INFO,
+                value: block.text().collect::<String>(),
+            };
             let block_name = block
                 .prev_sibling_element()
-                .ok_or("failed to find block")?
+                .ok_or(Error::MissingElement {
+                    field: "profile block heading",
+                    selector: PROFILE_INFO,
+                })?
                 .text()
                 .collect::<String>();
-            match block_name.as_str() {
-                "Race/Clan/Gender" => {
+            match locale.heading(&block_name) {
+                Some(Heading::RaceClanGender) => {
                     let (race, clan_gender) = match block.text().collect::<Vec<&str>>()[..] {
-                        [race, clan_gender] => (race.to_string(), clan_gender),
-                        _ => Err("failed to find race/clan/gender")?,
+                        [race, clan_gender] => (locale.english(race), clan_gender),
+                        _ => Err(invalid("race/clan/gender"))?,
                     };
                     let (clan, gender) = match clan_gender
                         .split('/')
                         .map(|t| t.trim())
                         .collect::<Vec<&str>>()[..]
                     {
-                        [clan, gender] => (clan.to_string(), gender.to_string()),
-                        _ => Err("failed to parse clan/gender")?,
+                        [clan, gender] => (locale.english(clan), gender.to_string()),
+                        _ => Err(invalid("clan/gender"))?,
                     };
                     info_blocks.insert("race", race);
                     info_blocks.insert("clan", clan);
                     info_blocks.insert("gender", gender);
                 }
-                "Guardian" => {
-                    let guardian = block.text().collect::<String>();
+                Some(Heading::Guardian) => {
+                    let guardian = locale.english(&block.text().collect::<String>());
                     info_blocks.insert("guardian", guardian);
                 }
-                "City-state" => {
-                    let city_state = block.text().collect::<String>();
+                Some(Heading::CityState) => {
+                    let city_state = locale.english(&block.text().collect::<String>());
                     info_blocks.insert("city_state", city_state);
                 }
-                "Grand Company" => {
+                Some(Heading::GrandCompany) => {
                     let (grand_company, grand_company_rank) = match block
                         .text()
                         .collect::<String>()
@@ -129,107 +146,89 @@ impl Profile {
                         .map(|t| t.trim())
                         .collect::<Vec<&str>>()[..]
                     {
-                        [grand_company, grand_company_rank] => {
-                            (grand_company.to_string(), grand_company_rank.to_string())
-                        }
-                        _ => Err("failed to parse grand company + grand_company rank")?,
+                        [grand_company, grand_company_rank] => (
+                            locale.english(grand_company),
+                            locale.english(grand_company_rank),
+                        ),
+                        _ => Err(invalid("grand company"))?,
                     };
                     info_blocks.insert("grand_company", grand_company);
                     info_blocks.insert("grand_company_rank", grand_company_rank);
                 }
-                _ => Err("encountered unknown profile element")?,
+                None => Err(Error::InvalidElement {
+                    field: "profile block heading",
+                    selector: PROFILE_INFO,
+                    value: block_name,
+                })?,
             }
         }
-        let race = info_blocks
-            .get("race")
-            .ok_or("failed to find race")?
-            .to_string();
-        let clan = info_blocks
-            .get("clan")
-            .ok_or("failed to find clan")?
-            .to_string();
-        let gender = info_blocks
-            .get("gender")
-            .ok_or("failed to find gender")?
-            .to_string();
+        let race = info_blocks.remove("race").ok_or(Error::MissingElement {
+            field: "race",
+            selector: PROFILE_INFO,
+        })?;
+        let clan = info_blocks.remove("clan").ok_or(Error::MissingElement {
+            field: "clan",
+            selector: PROFILE_INFO,
+        })?;
+        let gender = info_blocks.remove("gender").ok_or(Error::MissingElement {
+            field: "gender",
+            selector: PROFILE_INFO,
+        })?;
         let guardian = info_blocks
-            .get("guardian")
-            .ok_or("failed to find guardian")?
-            .to_string();
+            .remove("guardian")
+            .ok_or(Error::MissingElement {
+                field: "guardian",
+                selector: PROFILE_INFO,
+            })?;
         let city_state = info_blocks
-            .get("city_state")
-            .ok_or("failed to find city_state")?
-            .to_string();
+            .remove("city_state")
+            .ok_or(Error::MissingElement {
+                field: "city_state",
+                selector: PROFILE_INFO,
+            })?;
         let grand_company = info_blocks.remove("grand_company");
         let grand_company_rank = info_blocks.remove("grand_company_rank");
-        let hp = profile_html
-            .select(&select_hp)
-            .next()
-            .ok_or("couldn't find hp")?
-            .text()
-            .collect::<String>()
-            .parse::<u64>()
-            .map_err(|e| e.to_string())?;
-        let mp = profile_html
-            .select(&select_mp)
-            .next()
-            .ok_or("couldn't find mp")?
-            .text()
-            .collect::<String>()
-            .parse::<u64>()
-            .map_err(|e| e.to_string())?;
+        let hp = parse_number(select_text(profile, locale.hp, "hp")?, locale.hp, "hp")?;
+        let mp = parse_number(select_text(profile, locale.mp, "mp")?, locale.mp, "mp")?;
+        // the active job is only shown as an icon, so identify it by gear
+        const SOUL_CRYSTAL: &str = "div.icon-c--13 h2.db-tooltip__item__name";
+        const WEAPON: &str = "div.icon-c--0 p.db-tooltip__item__category";
+
+        let first_text = |css| -> Result<Option<String>, Error> {
+            Ok(profile
+                .select(&selector(css)?)
+                .next()
+                .map(|element| element.text().collect::<String>()))
+        };
+        let active_job = locale.active_job(
+            first_text(SOUL_CRYSTAL)?.as_deref(),
+            first_text(WEAPON)?.as_deref(),
+        );
+        let active_level = parse_active_level(profile)?;
+        let attributes = attributes::parse(profile, locale)?;
+        let equipment = gear::parse(profile)?;
 
         // extract job info page
-        let select_jobs = Selector::parse("ul.character__job li").map_err(|e| e.to_string())?;
-        let select_level =
-            Selector::parse("div.character__job__level").map_err(|e| e.to_string())?;
-        let select_job_name =
-            Selector::parse("div.character__job__name").map_err(|e| e.to_string())?;
-        let select_exp = Selector::parse("div.character__job__exp").map_err(|e| e.to_string())?;
+        const JOBS: &str = "ul.character__job li";
+        const LEVEL: &str = "div.character__job__level";
+        const JOB_NAME: &str = "div.character__job__name";
+        const EXP: &str = "div.character__job__exp";
 
         let mut snapshots: Vec<JobSnapshot> = vec![];
-        for job_details in jobs_html.select(&select_jobs) {
-            let level_string = job_details
-                .select(&select_level)
-                .next()
-                .ok_or("couldn't find level")?
-                .text()
-                .collect::<String>();
-            let level = match level_string.as_str() {
-                "-" => None,
-                some => some.replace(',', "").trim().parse::<u64>().ok(),
-            };
-            let job_name = job_details
-                .select(&select_job_name)
-                .next()
-                .ok_or("couldn't find job name")?
-                .text()
-                .collect::<String>();
-            let job = Job::from_str(&job_name).map_err(|e| e.to_string())?;
-            let exp_string = job_details
-                .select(&select_exp)
-                .next()
-                .ok_or("couldn't find exp")?
-                .text()
-                .collect::<String>();
-            let exp_parts: Vec<Option<u64>> = exp_string
-                .split('/')
-                .map(|part| match part {
-                    "--" => None,
-                    some => some.replace(',', "").trim().parse::<u64>().ok(),
-                })
-                .collect();
-            let exp = match &exp_parts[..] {
-                &[Some(current), Some(next)] => Some((current, next)),
-                _ => None,
-            };
+        for job_details in jobs_html.select(&selector(JOBS)?) {
+            let level = parse_level(&select_text(job_details, LEVEL, "level")?);
+            let job_name = select_text(job_details, JOB_NAME, "job name")?;
+            let job = locale.job(&job_name).ok_or(Error::UnknownJob(job_name))?;
+            let exp = parse_exp(&select_text(job_details, EXP, "exp")?);
             snapshots.push(JobSnapshot { job, level, exp });
         }
         let jobs = PlayerJobSnapshot::try_from(snapshots)?;
+        let content = content::parse(jobs_html.root_element(), locale)?;
         Ok(Profile {
-            jobs,
             user_id,
+            captured_at,
             free_company,
+            free_company_id,
             name,
             nameday,
             guardian,
@@ -242,17 +241,152 @@ impl Profile {
             grand_company_rank,
             hp,
             mp,
+            active_job,
+            active_level,
+            attributes,
+            equipment,
+            jobs,
+            content,
+        })
+    }
+}
+
+/// Download a lodestone page, with optional query parameters.
+pub fn fetch(url: &str, query: &[(&str, &str)]) -> Result<Html, Error> {
+    let text = query
+        .iter()
+        .fold(ureq::get(url), |request, (name, value)| {
+            request.query(name, value)
+        })
+        .call()?
+        .into_string()
+        .map_err(|e| Error::Network {
+            url: url.to_string(),
+            message: e.to_string(),
+        })?;
+    Ok(Html::parse_document(&text))
+}
+
+/// Whether a paginated lodestone list has a page after this one. The next page
+/// button is disabled on the last page.
+pub fn has_next_page(page: &Html) -> Result<bool, Error> {
+    const NEXT: &str = "a.btn__pager__next";
+
+    Ok(page.select(&selector(NEXT)?).next().is_some_and(|next| {
+        !next
+            .value()
+            .classes()
+            .any(|class| class == "btn__pager__no")
+            && next
+                .value()
+                .attr("href")
+                .is_some_and(|href| href.contains("page="))
+    }))
+}
+
+/// The ID at the end of a lodestone link, such as
+/// `/lodestone/character/38598907/`.
+pub fn link_id(href: &str) -> Option<u64> {
+    href.trim_end_matches('/').rsplit('/').next()?.parse().ok()
+}
+
+pub fn selector(css: &'static str) -> Result<Selector, Error> {
+    Selector::parse(css).map_err(|_| Error::Selector(css))
+}
+
+/// Text of the first element within `parent` matching `css`.
+pub fn select_text(
+    parent: ElementRef,
+    css: &'static str,
+    field: &'static str,
+) -> Result<String, Error> {
+    Ok(parent
+        .select(&selector(css)?)
+        .next()
+        .ok_or(Error::MissingElement {
+            field,
+            selector: css,
+        })?
+        .text()
+        .collect::<String>())
+}
+
+/// Attribute of the first element within `parent` matching `css`.
+pub fn select_attr(
+    parent: ElementRef,
+    css: &'static str,
+    attribute: &str,
+    field: &'static str,
+) -> Result<String, Error> {
+    parent
+        .select(&selector(css)?)
+        .next()
+        .and_then(|element| element.value().attr(attribute))
+        .map(|value| value.to_string())
+        .ok_or(Error::MissingElement {
+            field,
+            selector: css,
+        })
+}
+
+/// A level from the class/job page, shown as `-` when locked.
+pub fn parse_level(text: &str) -> Option<u64> {
+    match text {
+        "-" => None,
+        some => some.replace(',', "").trim().parse::<u64>().ok(),
+    }
+}
+
+/// Experience from the class/job page, shown as `current / next` or as
+/// `-- / --` when there is none.
+pub fn parse_exp(text: &str) -> Option<(u64, u64)> {
+    let parts: Vec<Option<u64>> = text
+        .split('/')
+        .map(|part| match part {
+            "--" => None,
+            some => some.replace(',', "").trim().parse::<u64>().ok(),
         })
+        .collect();
+    match &parts[..] {
+        &[Some(current), Some(next)] => Some((current, next)),
+        _ => None,
     }
 }
 
+/// The active job's level from the profile page, shown with a localized
+/// prefix such as `LEVEL 90` or `レベル 90`.
+fn parse_active_level(profile: ElementRef) -> Result<Option<u64>, Error> {
+    const ACTIVE_LEVEL: &str = "div.character__class__data p";
+
+    Ok(profile
+        .select(&selector(ACTIVE_LEVEL)?)
+        .next()
+        .and_then(|element| {
+            element
+                .text()
+                .flat_map(str::chars)
+                .filter(char::is_ascii_digit)
+                .collect::<String>()
+                .parse::<u64>()
+                .ok()
+        }))
+}
+
+fn parse_number(text: String, css: &'static str, field: &'static str) -> Result<u64, Error> {
+    text.parse::<u64>().map_err(|_| Error::InvalidElement {
+        field,
+        selector: css,
+        value: text,
+    })
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::gear::GearSlot;
     use include_dir::{include_dir, Dir};
-    use std::path::Path;
 
-    const PROFILES: &'static str = include_str!("tests/test-profiles.kdl");
+    const PROFILES: &str = include_str!("tests/test-profiles.kdl");
     static TEST_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/src/tests");
 
     /// Lodestone profile references included in test automation.
@@ -278,8 +412,15 @@ mod test {
     /// ```
     /// $ cargo run -p tasks setup-tests ./src/tests
     /// ```
+    ///
+    /// The snapshots are the `Debug` output of [`Profile`], so they must be
+    /// regenerated alongside any change to its fields:
+    ///
+    /// ```
+    /// $ cargo insta test --review -p ffxiv-tracker -- parse_profiles
+    /// ```
     #[test]
-    fn parse_profiles() -> Result<(), String> {
+    fn parse_profiles() -> Result<(), Error> {
         let profiles = knuffel::parse::<Vec<TestProfile>>("test-profiles.kdl", PROFILES).unwrap();
 
         for profile in profiles {
@@ -293,14 +434,48 @@ mod test {
                 .unwrap()
                 .contents_utf8()
                 .unwrap();
-            let profile_html = Html::parse_document(&text_profile);
-            let jobs_html = Html::parse_document(&text_jobs);
-            insta::assert_debug_snapshot!(
-                profile.name,
-                Profile::parse(profile.id, profile_html, jobs_html)?
-            );
+            let profile_html = Html::parse_document(text_profile);
+            let jobs_html = Html::parse_document(text_jobs);
+            let parsed = Profile::parse(
+                profile.id,
+                DateTime::UNIX_EPOCH,
+                Language::English,
+                profile_html,
+                jobs_html,
+            )?;
+
+            // the average can't beat the best item, and is known whenever the
+            // main hand's item level is
+            let average = gear::average_item_level(&parsed.equipment);
+            let best = parsed
+                .equipment
+                .iter()
+                .filter_map(|item| item.item_level)
+                .max();
+            let main_hand = parsed
+                .equipment
+                .iter()
+                .any(|item| item.slot == GearSlot::MainHand && item.item_level.is_some());
+            assert_eq!(average.is_some(), main_hand, "{}", profile.name);
+            assert!(average <= best, "{}", profile.name);
+
+            insta::assert_debug_snapshot!(profile.name, parsed);
         }
 
         Ok(())
     }
+    #[test]
+    fn active_level() -> Result<(), Error> {
+        let parse = |html: &str| parse_active_level(Html::parse_fragment(html).root_element());
+        let level =
+            |text: &str| format!(r#"<div class="character__class__data"><p>{text}</p></div>"#);
+
+        assert_eq!(parse(&level("LEVEL 90"))?, Some(90));
+        assert_eq!(parse(&level("NIVEAU 100"))?, Some(100));
+        assert_eq!(parse(&level("Stufe 5"))?, Some(5));
+        assert_eq!(parse(&level("レベル 70"))?, Some(70));
+        assert_eq!(parse(&level("LEVEL"))?, None);
+        assert_eq!(parse("<div><p>LEVEL 90</p></div>")?, None);
+        Ok(())
+    }
 }
@@ -0,0 +1,94 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// A regional lodestone site. Characters can be fetched from any of them, but
+/// each site's pages are in its own language.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Region {
+    #[default]
+    Na,
+    Eu,
+    Jp,
+    Fr,
+    De,
+}
+
+impl Region {
+    pub const ALL: [Region; 5] = [Region::Na, Region::Eu, Region::Jp, Region::Fr, Region::De];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Region::Na => "na",
+            Region::Eu => "eu",
+            Region::Jp => "jp",
+            Region::Fr => "fr",
+            Region::De => "de",
+        }
+    }
+
+    /// Base URL of the lodestone site, without a trailing slash.
+    pub fn lodestone_url(&self) -> String {
+        format!("https://{}.finalfantasyxiv.com/lodestone", self.code())
+    }
+
+    /// Base URL for character pages, without a trailing slash.
+    pub fn character_url(&self) -> String {
+        format!("{}/character", self.lodestone_url())
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl FromStr for Region {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Region::ALL
+            .into_iter()
+            .find(|region| region.code().eq_ignore_ascii_case(input))
+            .ok_or(format!(
+                "unknown region {input}, expected one of na/eu/jp/fr/de"
+            ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn region_urls() {
+        let expected = [
+            (Region::Na, "na"),
+            (Region::Eu, "eu"),
+            (Region::Jp, "jp"),
+            (Region::Fr, "fr"),
+            (Region::De, "de"),
+        ];
+        assert_eq!(expected.map(|(region, _)| region), Region::ALL);
+        for (region, code) in expected {
+            assert_eq!(region.code(), code);
+            assert_eq!(region.to_string(), code);
+            assert_eq!(Region::from_str(code), Ok(region));
+            assert_eq!(Region::from_str(&code.to_uppercase()), Ok(region));
+            assert_eq!(
+                region.lodestone_url(),
+                format!("https://{code}.finalfantasyxiv.com/lodestone")
+            );
+            assert_eq!(
+                region.character_url(),
+                format!("https://{code}.finalfantasyxiv.com/lodestone/character")
+            );
+        }
+
+        assert_eq!(
+            Region::from_str("us"),
+            Err("unknown region us, expected one of na/eu/jp/fr/de".to_string())
+        );
+        assert_eq!(Region::default(), Region::Na);
+    }
+}
@@ -0,0 +1,231 @@
+use crate::db::{CharacterRecord, ExperienceRecord, TrackerDatabase};
+use crate::error::Error;
+use crate::experience;
+use crate::job::{Job, Role};
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use strum::IntoEnumIterator;
+
+/// Page template, with `<!--CHART_JS-->` standing in for [`CHART_JS`],
+/// `/*STYLE*/` for [`STYLE`], `/*CHARTS*/` for the chart script and `/*DATA*/`
+/// for the chart data.
+const TEMPLATE: &str = include_str!("web/render.html");
+
+/// Standalone page template, with the same placeholders as [`TEMPLATE`] except
+/// `/*METADATA*/` for the job metadata in place of the chart data.
+const BUNDLE_TEMPLATE: &str = include_str!("web/bundle.html");
+
+/// Script drawing the charts, shared by every page with charts.
+pub const CHARTS: &str = include_str!("web/charts.js");
+
+/// Tag loading Chart.js, which [`CHARTS`] draws with.
+pub const CHART_JS: &str =
+    r#"<script src="https://cdn.jsdelivr.net/npm/chart.js@4.4.3/dist/chart.umd.min.js"></script>"#;
+
+/// Style of the charts and their toggles.
+pub const STYLE: &str = include_str!("web/charts.css");
+
+/// Name of the database the standalone page loads from beside itself.
+pub const BUNDLE_DATABASE: &str = "ffxiv-tracker.sqlite";
+
+/// Render a page charting the level and experience of each job over time, for
+/// each of `user_ids`.
+///
+/// The chart data is inlined, so the page works as a single file. Chart.js is
+/// loaded from a CDN.
+pub fn render(
+    database: &TrackerDatabase,
+    user_ids: &[u64],
+    generated_at: DateTime<Utc>,
+) -> Result<String, Error> {
+    let characters = database.characters()?;
+    let mut charts: Vec<Value> = vec![];
+    for &user_id in user_ids {
+        let character = characters
+            .iter()
+            .find(|character| character.user_id == user_id)
+            .ok_or(Error::NoSnapshot(format!("of {user_id}")))?;
+        let history = database.history(user_id, None, None, None)?;
+        charts.push(character_chart(character, &history));
+    }
+    let data = chart_data(charts, generated_at);
+    Ok(TEMPLATE
+        .replace("<!--CHART_JS-->", CHART_JS)
+        .replace("/*STYLE*/", STYLE)
+        .replace("/*CHARTS*/", CHARTS)
+        .replace("/*DATA*/", &inline_json(&data)))
+}
+
+/// The data [`CHARTS`] draws, from the [`character_chart`] of each character.
+pub fn chart_data(characters: Vec<Value>, generated_at: DateTime<Utc>) -> Value {
+    let roles: Vec<Value> = Role::iter()
+        .map(|role| json!({ "role": role.to_string(), "color": role_color(role) }))
+        .collect();
+    json!({
+        "generated_at": generated_at.to_rfc3339(),
+        "roles": roles,
+        "characters": characters,
+    })
+}
+
+/// The charts of one character, from their experience history.
+pub fn character_chart(
+    character: &CharacterRecord,
+    history: &BTreeMap<Job, Vec<ExperienceRecord>>,
+) -> Value {
+    let mut jobs: Vec<Value> = vec![];
+    for (&job, records) in history {
+        // jobs which were never unlocked would only clutter the charts
+        if records.iter().all(|record| record.level.is_none()) {
+            continue;
+        }
+        // snapshots with an unknown capture time can't be placed on a chart
+        let points: Vec<Value> = records
+            .iter()
+            .filter_map(|record| {
+                Some(json!({
+                    "t": record.captured_at?.timestamp_millis(),
+                    "level": record.level,
+                    "total_xp": experience::total_exp(job, record.level, record.exp),
+                }))
+            })
+            .collect();
+        jobs.push(json!({
+            "job": job.to_string(),
+            "role": job.role().to_string(),
+            "color": color(job),
+            "points": points,
+        }));
+    }
+    json!({
+        "user_id": character.user_id,
+        "name": character.name,
+        "server": character.server,
+        "jobs": jobs,
+    })
+}
+
+/// Render a standalone page which charts a tracker database loaded in the
+/// browser, either [`BUNDLE_DATABASE`] published beside it or a file chosen
+/// by the user.
+///
+/// The database is queried with sql.js, so the page can be hosted without a
+/// backend. Job colors and the experience table are inlined, to chart the
+/// same values as [`render`]. Chart.js and sql.js are loaded from a CDN.
+pub fn bundle() -> String {
+    let roles: Vec<Value> = Role::iter()
+        .map(|role| json!({ "role": role.to_string(), "color": role_color(role) }))
+        .collect();
+    let jobs: Vec<Value> = Job::iter()
+        .map(|job| {
+            json!({
+                "job": job.to_string(),
+                "role": job.role().to_string(),
+                "color": color(job),
+                "level_cap": job.level_cap(),
+            })
+        })
+        .collect();
+    let exp_to_reach: Vec<u64> = (1..=experience::EXP_TO_NEXT.len() as u64 + 1)
+        .filter_map(experience::exp_to_reach)
+        .collect();
+    let metadata = json!({
+        "database": BUNDLE_DATABASE,
+        "roles": roles,
+        "jobs": jobs,
+        "exp_to_reach": exp_to_reach,
+    });
+    BUNDLE_TEMPLATE
+        .replace("<!--CHART_JS-->", CHART_JS)
+        .replace("/*STYLE*/", STYLE)
+        .replace("/*CHARTS*/", CHARTS)
+        .replace("/*METADATA*/", &inline_json(&metadata))
+}
+
+/// Serialize JSON to inline in a script element.
+///
+/// `<` only appears inside JSON strings, where it can be escaped so that
+/// names can't close the script element.
+pub fn inline_json(value: &Value) -> String {
+    value.to_string().replace('<', "\\u003c")
+}
+
+/// Hue of each role's colors, roughly following the in-game role colors.
+fn hue(role: Role) -> u32 {
+    match role {
+        Role::Tank => 220,
+        Role::Healer => 130,
+        Role::Dps => 0,
+        Role::DiscipleOfHand => 275,
+        Role::DiscipleOfLand => 40,
+    }
+}
+
+fn role_color(role: Role) -> String {
+    format!("hsl({}, 65%, 45%)", hue(role))
+}
+
+/// A color for each job, in shades of its role's hue.
+fn color(job: Job) -> String {
+    let role = job.role();
+    let peers: Vec<Job> = Job::iter().filter(|other| other.role() == role).collect();
+    let index = peers.iter().position(|other| *other == job).unwrap_or(0);
+    let lightness = 30 + 40 * index / peers.len().max(1);
+    format!("hsl({}, 65%, {lightness}%)", hue(role))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::db::test::{test_database, test_profile};
+
+    #[test]
+    fn render_page() -> Result<(), Error> {
+        let mut database = test_database();
+        let mut profile = test_profile(DateTime::UNIX_EPOCH);
+        profile.name = "</script><script>alert(1)".to_string();
+        if let Some(sage) = profile.jobs.0.get_mut(&Job::Sage) {
+            sage.level = None;
+            sage.exp = None;
+        }
+        database.snapshot(profile)?;
+
+        let page = render(&database, &[1], DateTime::UNIX_EPOCH)?;
+        let start = page.find(r#"type="application/json">"#).unwrap() + 24;
+        let end = start + page[start..].find("</script>").unwrap();
+        let data: Value = serde_json::from_str(&page[start..end]).unwrap();
+
+        let character = &data["characters"][0];
+        assert_eq!(character["name"], "</script><script>alert(1)");
+        assert_eq!(character["jobs"][0]["job"], "Paladin");
+        assert_eq!(character["jobs"][0]["role"], "Tank");
+        assert_eq!(character["jobs"][0]["points"][0]["t"], 0);
+        assert_eq!(character["jobs"][0]["points"][0]["level"], 1);
+        let jobs = character["jobs"].as_array().unwrap();
+        assert!(!jobs.iter().any(|job| job["job"] == "Sage"));
+        assert_ne!(color(Job::Paladin), color(Job::Warrior));
+
+        assert!(render(&database, &[2], DateTime::UNIX_EPOCH).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn bundle_page() {
+        let page = bundle();
+        assert!(page.contains("function drawCharts"));
+        assert!(page.contains(CHART_JS) && page.contains(".charts {"));
+        let start = page.find(r#"type="application/json">"#).unwrap() + 24;
+        let end = start + page[start..].find("</script>").unwrap();
+        let metadata: Value = serde_json::from_str(&page[start..end]).unwrap();
+
+        assert_eq!(metadata["database"], BUNDLE_DATABASE);
+        assert_eq!(metadata["jobs"][0]["job"], "Paladin");
+        assert_eq!(metadata["jobs"][0]["color"], color(Job::Paladin));
+        assert_eq!(metadata["jobs"][0]["level_cap"], 100);
+        // indexed from level 1
+        assert_eq!(metadata["exp_to_reach"][0], 0);
+        assert_eq!(metadata["exp_to_reach"][2], 900);
+        assert_eq!(metadata["exp_to_reach"].as_array().unwrap().len(), 100);
+    }
+}
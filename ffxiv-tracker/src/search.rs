@@ -0,0 +1,139 @@
+use crate::error::Error;
+use crate::profile::{fetch, link_id, select_attr, select_text, selector};
+use crate::region::Region;
+use scraper::Html;
+
+/// A character listed on the lodestone character search page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    pub id: u64,
+    pub name: String,
+    /// Home world and data center, e.g. `Adamantoise [Aether]`.
+    pub world: String,
+    pub portrait: String,
+    pub free_company: Option<String>,
+}
+
+impl SearchResult {
+    /// Whether the character is on `world`, given with or without its data
+    /// center.
+    pub fn on_world(&self, world: &str) -> bool {
+        let home = self.world.split(" [").next().unwrap_or_default();
+        home.eq_ignore_ascii_case(world.trim()) || self.world.eq_ignore_ascii_case(world.trim())
+    }
+}
+
+/// Search lodestone for characters by name, optionally on a single world.
+///
+/// Only the first page of results is returned. Lodestone matches names
+/// loosely, so results may include characters with similar names.
+pub fn search(name: &str, world: Option<&str>, region: Region) -> Result<Vec<SearchResult>, Error> {
+    let url = format!("{}/", region.character_url());
+    let html = fetch(
+        &url,
+        &[("q", name), ("worldname", world.unwrap_or_default())],
+    )?;
+    parse(&html)
+}
+
+/// Find the single character named exactly `name` on `world`.
+pub fn resolve(name: &str, world: &str, region: Region) -> Result<SearchResult, Error> {
+    let mut matches: Vec<SearchResult> = search(name, Some(world), region)?
+        .into_iter()
+        .filter(|result| result.name.eq_ignore_ascii_case(name.trim()) && result.on_world(world))
+        .collect();
+    match matches.len() {
+        0 => Err(Error::NoCharacter(format!("{name} @ {world}"))),
+        1 => Ok(matches.remove(0)),
+        _ => Err(Error::AmbiguousCharacter {
+            name: format!("{name} @ {world}"),
+            ids: matches.iter().map(|result| result.id).collect(),
+        }),
+    }
+}
+
+fn parse(html: &Html) -> Result<Vec<SearchResult>, Error> {
+    const ENTRY: &str = "div.ldst__window div.entry";
+    const LINK: &str = "a.entry__link";
+    const NAME: &str = "p.entry__name";
+    const WORLD: &str = "p.entry__world";
+    const PORTRAIT: &str = "div.entry__chara__face img";
+    const FREE_COMPANY: &str = "a.entry__freecompany__link span";
+
+    let mut results: Vec<SearchResult> = vec![];
+    for entry in html.select(&selector(ENTRY)?) {
+        let href = select_attr(entry, LINK, "href", "character link")?;
+        let id = link_id(&href).ok_or(Error::InvalidElement {
+            field: "character link",
+            selector: LINK,
+            value: href.clone(),
+        })?;
+        let free_company = entry
+            .select(&selector(FREE_COMPANY)?)
+            .next()
+            .map(|element| element.text().collect::<String>());
+        results.push(SearchResult {
+            id,
+            name: select_text(entry, NAME, "name")?,
+            world: select_text(entry, WORLD, "world")?.trim().to_string(),
+            portrait: select_attr(entry, PORTRAIT, "src", "portrait")?,
+            free_company,
+        });
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_search_results() -> Result<(), Error> {
+        let html = Html::parse_document(
+            r#"
+            <div class="ldst__window">
+              <div class="entry">
+                <a href="/lodestone/character/38598907/" class="entry__link">
+                  <div class="entry__chara__face"><img src="https://img2.finalfantasyxiv.com/f/a.jpg"></div>
+                  <div class="entry__box entry__box--world">
+                    <p class="entry__name">Test Player</p>
+                    <p class="entry__world"><i class="xiv-lds"></i>Adamantoise [Aether]</p>
+                  </div>
+                </a>
+                <a href="/lodestone/freecompany/9229142273877347048/" class="entry__freecompany__link">
+                  <span>Test Company</span>
+                </a>
+              </div>
+              <div class="entry">
+                <a href="/lodestone/character/26351638/" class="entry__link">
+                  <div class="entry__chara__face"><img src="https://img2.finalfantasyxiv.com/f/b.jpg"></div>
+                  <div class="entry__box entry__box--world">
+                    <p class="entry__name">Test Players</p>
+                    <p class="entry__world">Cactuar [Aether]</p>
+                  </div>
+                </a>
+              </div>
+            </div>
+            "#,
+        );
+
+        let results = parse(&html)?;
+        assert_eq!(
+            results[0],
+            SearchResult {
+                id: 38598907,
+                name: "Test Player".to_string(),
+                world: "Adamantoise [Aether]".to_string(),
+                portrait: "https://img2.finalfantasyxiv.com/f/a.jpg".to_string(),
+                free_company: Some("Test Company".to_string()),
+            }
+        );
+        assert_eq!(results[1].id, 26351638);
+        assert_eq!(results[1].free_company, None);
+        assert!(results[0].on_world("adamantoise"));
+        assert!(results[0].on_world("Adamantoise [Aether]"));
+        assert!(!results[1].on_world("Adamantoise"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,361 @@
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+use crate::config::parse_time;
+use crate::db::{SnapshotOutcome, TrackerDatabase};
+use crate::diff;
+use crate::error::Error;
+use crate::job::Job;
+use crate::json;
+use crate::profile::Profile;
+use crate::region::Region;
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use tracing::{event, Level};
+
+/// Answers API requests from a tracker database.
+///
+/// Profiles are downloaded with `fetch`, so that the API can be exercised
+/// without reaching lodestone.
+pub struct Api<F> {
+    database: TrackerDatabase,
+    /// Characters which may be snapshot on request, and the site to fetch
+    /// each from.
+    tracked: Vec<(u64, Region)>,
+    fetch: F,
+}
+
+/// A JSON response to an API request.
+#[derive(Debug)]
+pub struct Response {
+    pub status: u16,
+    pub body: Value,
+}
+
+/// Why a request failed, as an HTTP status and a message.
+struct Failure {
+    status: u16,
+    message: String,
+}
+
+impl Failure {
+    fn new(status: u16, message: impl Into<String>) -> Failure {
+        Failure {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<Error> for Failure {
+    fn from(error: Error) -> Failure {
+        let status = match &error {
+            Error::NoSnapshot(_) | Error::NoCharacter(_) => 404,
+            Error::MismatchedSnapshots { .. } => 400,
+            // lodestone failed, rather than the tracker
+            Error::Http { .. } | Error::Network { .. } => 502,
+            _ => 500,
+        };
+        Failure::new(status, error.to_string())
+    }
+}
+
+impl<F> Api<F>
+where
+    F: FnMut(u64, Region) -> Result<Profile, Error>,
+{
+    pub fn new(database: TrackerDatabase, tracked: Vec<(u64, Region)>, fetch: F) -> Api<F> {
+        Api {
+            database,
+            tracked,
+            fetch,
+        }
+    }
+
+    /// Answer a request for `url`, a path with an optional query string.
+    ///
+    /// - `GET /characters`: every character with a snapshot
+    /// - `GET /characters/{id}`: their latest snapshot
+    /// - `GET /characters/{id}/history`: experience per job, optionally with
+    ///   `job`, `since` and `until`
+    /// - `GET /characters/{id}/diff`: changes between the snapshots at
+    ///   `since` and `until` (or between snapshot IDs `from` and `to`), by
+    ///   default the latest two
+    /// - `POST /characters/{id}/snapshot`: snapshot a tracked character now
+    pub fn handle(&mut self, method: &str, url: &str) -> Response {
+        match self.route(method, url) {
+            Ok(body) => Response { status: 200, body },
+            Err(failure) => Response {
+                status: failure.status,
+                body: json!({ "error": failure.message }),
+            },
+        }
+    }
+
+    fn route(&mut self, method: &str, url: &str) -> Result<Value, Failure> {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        let query = parse_query(query);
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+
+        match (method, &segments[..]) {
+            ("GET", ["characters"]) => self.characters(),
+            ("GET", ["characters", user_id]) => self.latest(parse_id(user_id)?),
+            ("GET", ["characters", user_id, "history"]) => self.history(parse_id(user_id)?, &query),
+            ("GET", ["characters", user_id, "diff"]) => self.diff(parse_id(user_id)?, &query),
+            ("POST", ["characters", user_id, "snapshot"]) => self.snapshot(parse_id(user_id)?),
+            _ => Err(Failure::new(404, format!("no endpoint {method} {path}"))),
+        }
+    }
+
+    fn characters(&self) -> Result<Value, Failure> {
+        let characters = self
+            .database
+            .characters()?
+            .iter()
+            .map(|character| {
+                json!({
+                    "user_id": character.user_id,
+                    "name": character.name,
+                    "server": character.server,
+                    "snapshot_id": character.snapshot_id,
+                    "captured_at": character.captured_at.map(|t| t.to_rfc3339()),
+                })
+            })
+            .collect();
+        Ok(Value::Array(characters))
+    }
+
+    fn latest(&self, user_id: u64) -> Result<Value, Failure> {
+        let latest = self
+            .database
+            .snapshots(user_id, None, None)?
+            .pop()
+            .ok_or(Error::NoSnapshot(format!("of {user_id}")))?;
+        Ok(json::snapshot(
+            &self.database.load_snapshot(latest.snapshot_id)?,
+        ))
+    }
+
+    fn history(&self, user_id: u64, query: &HashMap<String, String>) -> Result<Value, Failure> {
+        let job = match query.get("job") {
+            Some(job) => Some(
+                Job::from_str(job).map_err(|_| Failure::new(400, format!("unknown job: {job}")))?,
+            ),
+            None => None,
+        };
+        let since = time_param(query, "since")?;
+        let until = time_param(query, "until")?;
+        let history = self.database.history(user_id, job, since, until)?;
+        let content = match job {
+            Some(_) => BTreeMap::new(),
+            None => self.database.content_history(user_id, since, until)?,
+        };
+        Ok(json::history(&history, &content))
+    }
+
+    fn diff(&self, user_id: u64, query: &HashMap<String, String>) -> Result<Value, Failure> {
+        let id_param = |name: &str| match query.get(name) {
+            Some(id) => id
+                .parse::<i64>()
+                .map(Some)
+                .map_err(|_| Failure::new(400, format!("invalid {name}: {id}"))),
+            None => Ok(None),
+        };
+        let from = id_param("from")?;
+        let mut to = id_param("to")?;
+        let since = time_param(query, "since")?;
+        // compare up to the latest snapshot at or before `until`
+        if let Some(until) = time_param(query, "until")? {
+            let snapshot = self
+                .database
+                .snapshots(user_id, None, Some(until))?
+                .pop()
+                .ok_or(Error::NoSnapshot(format!("of {user_id} before {until}")))?;
+            to = Some(snapshot.snapshot_id);
+        }
+        let diff = diff::diff(&self.database, user_id, from, to, since)?;
+        Ok(json::diff(&diff))
+    }
+
+    fn snapshot(&mut self, user_id: u64) -> Result<Value, Failure> {
+        let Some(&(_, region)) = self.tracked.iter().find(|(id, _)| *id == user_id) else {
+            Err(Failure::new(404, format!("{user_id} isn't tracked")))?
+        };
+        event!(Level::INFO, "downloading profile {user_id}");
+        let profile = (self.fetch)(user_id, region)?;
+        let body = match self.database.snapshot(profile)? {
+            SnapshotOutcome::Created(snapshot_id) => {
+                json!({ "outcome": "created", "snapshot_id": snapshot_id })
+            }
+            SnapshotOutcome::Unchanged(snapshot_id) => {
+                json!({ "outcome": "unchanged", "snapshot_id": snapshot_id })
+            }
+        };
+        Ok(body)
+    }
+}
+
+/// Serve the API on `address` until the process is stopped.
+///
+/// Requests are answered one at a time, so a snapshot request holds up the
+/// others until its profile has downloaded.
+pub fn run(
+    address: &str,
+    database: TrackerDatabase,
+    tracked: Vec<(u64, Region)>,
+) -> Result<(), Error> {
+    let server = tiny_http::Server::http(address).map_err(|e| Error::Listen {
+        address: address.to_string(),
+        message: e.to_string(),
+    })?;
+    event!(Level::INFO, "listening on {address}");
+
+    let mut api = Api::new(database, tracked, Profile::get);
+    let content_type =
+        tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+            .expect("static header is valid");
+    for request in server.incoming_requests() {
+        let response = api.handle(request.method().as_str(), request.url());
+        event!(
+            Level::INFO,
+            "{} {}: {}",
+            request.method(),
+            request.url(),
+            response.status
+        );
+        let body =
+            serde_json::to_string_pretty(&response.body).expect("JSON values always serialize");
+        let http = tiny_http::Response::from_string(body)
+            .with_status_code(response.status)
+            .with_header(content_type.clone());
+        if let Err(e) = request.respond(http) {
+            event!(Level::WARN, "failed to respond: {e}");
+        }
+    }
+    Ok(())
+}
+
+fn parse_id(text: &str) -> Result<u64, Failure> {
+    text.parse::<u64>()
+        .map_err(|_| Failure::new(400, format!("invalid character ID: {text}")))
+}
+
+fn time_param(
+    query: &HashMap<String, String>,
+    name: &str,
+) -> Result<Option<DateTime<Utc>>, Failure> {
+    match query.get(name) {
+        Some(time) => parse_time(time)
+            .map(Some)
+            .map_err(|message| Failure::new(400, format!("invalid {name}: {message}"))),
+        None => Ok(None),
+    }
+}
+
+/// Parse a query string such as `job=Dark+Knight&since=2024-07-01`.
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(name), decode(value))
+        })
+        .collect()
+}
+
+/// Decode a percent-encoded query string component.
+fn decode(component: &str) -> String {
+    let bytes = component.as_bytes();
+    let mut decoded: Vec<u8> = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = bytes
+            .get(index + 1..index + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[index], escaped) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            (b'+', _) => {
+                decoded.push(b' ');
+                index += 1;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::db::test::{day, test_database, test_profile};
+
+    #[test]
+    fn serve_api() -> Result<(), Error> {
+        let mut database = test_database();
+        database.snapshot(test_profile(day(1)))?;
+        let mut leveled = test_profile(day(2));
+        if let Some(paladin) = leveled.jobs.0.get_mut(&Job::Paladin) {
+            paladin.level = Some(2);
+        }
+        database.snapshot(leveled)?;
+
+        let mut api = Api::new(database, vec![(1, Region::Na)], |user_id, _| {
+            let mut profile = test_profile(day(3));
+            profile.user_id = user_id;
+            profile.server = "Cactuar [Aether]".to_string();
+            Ok(profile)
+        });
+
+        let characters = api.handle("GET", "/characters");
+        assert_eq!(characters.status, 200);
+        assert_eq!(characters.body[0]["name"], "Test Player");
+        assert_eq!(characters.body[0]["snapshot_id"], 2);
+
+        let latest = api.handle("GET", "/characters/1");
+        assert_eq!(latest.body["jobs"]["Paladin"]["level"], 2);
+
+        let history = api.handle("GET", "/characters/1/history?job=Paladin&since=1970-01-03");
+        assert_eq!(history.status, 200);
+        let paladin = history.body["Paladin"].as_array().unwrap();
+        assert_eq!(paladin.len(), 1);
+        assert_eq!(paladin[0]["level"], 2);
+        assert!(history.body.get("Warrior").is_none());
+
+        let diff = api.handle(
+            "GET",
+            "/characters/1/diff?since=1970-01-02&until=1970-01-03",
+        );
+        assert_eq!(diff.status, 200);
+        assert_eq!(diff.body["jobs"][0]["job"], "Paladin");
+        assert_eq!(diff.body["jobs"][0]["levels_gained"], 1);
+
+        let snapshot = api.handle("POST", "/characters/1/snapshot");
+        assert_eq!(snapshot.body["outcome"], "created");
+        assert_eq!(
+            api.handle("GET", "/characters/1").body["server"],
+            "Cactuar [Aether]"
+        );
+
+        // only tracked characters are fetched
+        assert_eq!(api.handle("POST", "/characters/2/snapshot").status, 404);
+        assert_eq!(api.handle("GET", "/characters/2").status, 404);
+        assert_eq!(api.handle("GET", "/characters/x").status, 400);
+        assert_eq!(
+            api.handle("GET", "/characters/1/history?job=Freelancer")
+                .status,
+            400
+        );
+        assert_eq!(api.handle("DELETE", "/characters").status, 404);
+        assert_eq!(decode("Dark+Knight%20%E2%99%82"), "Dark Knight ♂");
+
+        Ok(())
+    }
+}
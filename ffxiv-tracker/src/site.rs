@@ -0,0 +1,407 @@
+use crate::db::{CharacterRecord, ExperienceRecord, StoredSnapshot, TrackerDatabase};
+use crate::error::Error;
+use crate::job::{Job, Role};
+use crate::render::{self, character_chart, chart_data, inline_json, CHARTS, CHART_JS};
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use strum::IntoEnumIterator;
+
+/// Level-ups listed on each character page, most recent first.
+const RECENT_LEVEL_UPS: usize = 20;
+
+/// Records the snapshot each character page was generated from, so pages of
+/// characters without new snapshots can be skipped.
+const MANIFEST: &str = "site.json";
+
+/// Style of the tables and navigation, added to [`render::STYLE`].
+const STYLE: &str = "
+nav a { margin-right: 1em; }
+table { border-collapse: collapse; margin-bottom: 2em; }
+th, td { padding: 0.2em 0.6em; border-bottom: 1px solid #ddd; text-align: left; }
+td.number { text-align: right; }
+td.cap { font-weight: bold; }
+";
+
+/// A job gaining levels between two snapshots.
+#[derive(Debug, PartialEq)]
+pub struct LevelUp {
+    pub captured_at: DateTime<Utc>,
+    pub job: Job,
+    /// `None` if the job was unlocked.
+    pub from: Option<u64>,
+    pub to: u64,
+}
+
+/// Pages written by [`generate`].
+#[derive(Debug, Default, PartialEq)]
+pub struct Generated {
+    /// Characters whose pages were written.
+    pub characters: Vec<u64>,
+    /// Characters whose pages were already up to date.
+    pub unchanged: usize,
+}
+
+/// Generate a static site into `out_dir`: an index of every character's
+/// current levels, a page per character with their history, and a
+/// leaderboard for each free company.
+///
+/// Only pages of characters with new snapshots since the last run are
+/// written, unless `force` is given. The index and leaderboard are written
+/// whenever any character page is.
+pub fn generate(
+    database: &TrackerDatabase,
+    out_dir: &Path,
+    generated_at: DateTime<Utc>,
+    force: bool,
+) -> Result<Generated, Error> {
+    let manifest_path = out_dir.join(MANIFEST);
+    let previous = match force {
+        true => BTreeMap::new(),
+        false => read_manifest(&manifest_path)?,
+    };
+    let characters_dir = out_dir.join("characters");
+    std::fs::create_dir_all(&characters_dir).map_err(|source| Error::Io {
+        path: characters_dir.clone(),
+        source,
+    })?;
+
+    let characters = database.characters()?;
+    let mut generated = Generated::default();
+    for character in &characters {
+        let path = out_dir.join(character_path(character.user_id));
+        if previous.get(&character.user_id) == Some(&character.snapshot_id) && path.exists() {
+            generated.unchanged += 1;
+            continue;
+        }
+        write(&path, &character_page(database, character, generated_at)?)?;
+        generated.characters.push(character.user_id);
+    }
+
+    let index_path = out_dir.join("index.html");
+    let leaderboard_path = out_dir.join("leaderboard.html");
+    if !generated.characters.is_empty() || !index_path.exists() || !leaderboard_path.exists() {
+        let latest = characters
+            .iter()
+            .map(|character| database.load_snapshot(character.snapshot_id))
+            .collect::<Result<Vec<StoredSnapshot>, Error>>()?;
+        write(&index_path, &index_page(&latest, generated_at))?;
+        write(&leaderboard_path, &leaderboard_page(&latest, generated_at))?;
+    }
+
+    let snapshots: BTreeMap<String, i64> = characters
+        .iter()
+        .map(|character| (character.user_id.to_string(), character.snapshot_id))
+        .collect();
+    let manifest = json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "snapshots": snapshots,
+    });
+    write(&manifest_path, &manifest.to_string())?;
+    Ok(generated)
+}
+
+/// Levels gained by each job between consecutive snapshots, most recent
+/// first. Snapshots with an unknown capture time are skipped.
+pub fn level_ups(history: &BTreeMap<Job, Vec<ExperienceRecord>>) -> Vec<LevelUp> {
+    let mut level_ups: Vec<LevelUp> = vec![];
+    for (&job, records) in history {
+        for pair in records.windows(2) {
+            let (Some(to), Some(captured_at)) = (pair[1].level, pair[1].captured_at) else {
+                continue;
+            };
+            if pair[0].level.is_some_and(|from| from >= to) {
+                continue;
+            }
+            level_ups.push(LevelUp {
+                captured_at,
+                job,
+                from: pair[0].level,
+                to,
+            });
+        }
+    }
+    level_ups.sort_by(|a, b| b.captured_at.cmp(&a.captured_at).then(a.job.cmp(&b.job)));
+    level_ups
+}
+
+/// The snapshot each character page was generated from, or nothing if the
+/// manifest is missing or was written by another version.
+fn read_manifest(path: &Path) -> Result<BTreeMap<u64, i64>, Error> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(source) => Err(Error::Io {
+            path: path.to_path_buf(),
+            source,
+        })?,
+    };
+    let manifest: Value = serde_json::from_str(&text).unwrap_or(Value::Null);
+    if manifest["version"] != env!("CARGO_PKG_VERSION") {
+        return Ok(BTreeMap::new());
+    }
+    Ok(manifest["snapshots"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter_map(|(user_id, snapshot_id)| Some((user_id.parse().ok()?, snapshot_id.as_i64()?)))
+        .collect())
+}
+
+fn write(path: &Path, contents: &str) -> Result<(), Error> {
+    std::fs::write(path, contents).map_err(|source| Error::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn character_path(user_id: u64) -> PathBuf {
+    PathBuf::from("characters").join(format!("{user_id}.html"))
+}
+
+fn character_page(
+    database: &TrackerDatabase,
+    character: &CharacterRecord,
+    generated_at: DateTime<Utc>,
+) -> Result<String, Error> {
+    let history = database.history(character.user_id, None, None, None)?;
+    let level_ups = level_ups(&history);
+
+    let mut body = format!(
+        "<h1>{} <small>{}</small></h1>\n<h2>Recent level-ups</h2>\n",
+        escape(&character.name),
+        escape(&character.server),
+    );
+    if level_ups.is_empty() {
+        body.push_str("<p>No level-ups recorded yet.</p>\n");
+    } else {
+        body.push_str("<table>\n<tr><th>Date</th><th>Job</th><th>Level</th></tr>\n");
+        for level_up in level_ups.iter().take(RECENT_LEVEL_UPS) {
+            let levels = match level_up.from {
+                Some(from) => format!("{from} → {}", level_up.to),
+                None => format!("unlocked at {}", level_up.to),
+            };
+            body.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{levels}</td></tr>\n",
+                level_up.captured_at.format("%Y-%m-%d"),
+                level_up.job,
+            ));
+        }
+        body.push_str("</table>\n");
+    }
+
+    let data = chart_data(vec![character_chart(character, &history)], generated_at);
+    body.push_str(&format!(
+        "<h2>History</h2>
+<div id=\"toggles\"></div>
+<div id=\"characters\"></div>
+<script id=\"data\" type=\"application/json\">{}</script>
+<script>
+{CHARTS}
+drawCharts(JSON.parse(document.getElementById(\"data\").textContent));
+</script>
+",
+        inline_json(&data)
+    ));
+
+    Ok(layout(
+        &character.name,
+        "../",
+        CHART_JS,
+        &body,
+        generated_at,
+    ))
+}
+
+/// Current level of every job of every character, with a table per role.
+fn index_page(latest: &[StoredSnapshot], generated_at: DateTime<Utc>) -> String {
+    let mut body = String::from("<h1>Characters</h1>\n");
+    if latest.is_empty() {
+        body.push_str("<p>No characters have been snapshot yet.</p>\n");
+        return layout("Characters", "", "", &body, generated_at);
+    }
+    for role in Role::iter() {
+        let jobs: Vec<Job> = Job::iter().filter(|job| job.role() == role).collect();
+        body.push_str(&format!("<h2>{role}</h2>\n<table>\n<tr><th>Character</th>"));
+        for job in &jobs {
+            body.push_str(&format!("<th>{job}</th>"));
+        }
+        body.push_str("</tr>\n");
+        for snapshot in latest {
+            body.push_str(&format!("<tr><td>{}</td>", character_link(snapshot)));
+            for job in &jobs {
+                let level = snapshot.jobs.get(job).and_then(|job| job.level);
+                let class = match level {
+                    Some(level) if level >= job.level_cap() => "number cap",
+                    _ => "number",
+                };
+                let level = level.map_or("–".to_string(), |level| level.to_string());
+                body.push_str(&format!("<td class=\"{class}\">{level}</td>"));
+            }
+            body.push_str("</tr>\n");
+        }
+        body.push_str("</table>\n");
+    }
+    layout("Characters", "", "", &body, generated_at)
+}
+
+/// Members of each free company ranked by lifetime experience across all
+/// jobs. Characters outside a free company aren't ranked.
+fn leaderboard_page(latest: &[StoredSnapshot], generated_at: DateTime<Utc>) -> String {
+    let mut free_companies: BTreeMap<u64, Vec<&StoredSnapshot>> = BTreeMap::new();
+    for snapshot in latest {
+        if let Some(free_company_id) = snapshot.fields.free_company_id {
+            free_companies
+                .entry(free_company_id)
+                .or_default()
+                .push(snapshot);
+        }
+    }
+
+    let mut body = String::from("<h1>Leaderboard</h1>\n");
+    if free_companies.is_empty() {
+        body.push_str("<p>No characters are in a free company.</p>\n");
+    }
+    for members in free_companies.values_mut() {
+        let total = |snapshot: &StoredSnapshot| -> u64 {
+            snapshot
+                .jobs
+                .values()
+                .filter_map(|job| job.total_exp())
+                .sum()
+        };
+        members.sort_by_key(|snapshot| std::cmp::Reverse(total(snapshot)));
+
+        let name = members[0]
+            .fields
+            .free_company
+            .as_deref()
+            .unwrap_or("Free company");
+        body.push_str(&format!(
+            "<h2>{}</h2>\n<table>\n<tr><th>#</th><th>Character</th><th>Jobs at level cap</th>\
+            <th>Highest level</th><th>Total experience</th></tr>\n",
+            escape(name)
+        ));
+        for (rank, snapshot) in members.iter().enumerate() {
+            let capped = snapshot
+                .jobs
+                .values()
+                .filter(|job| job.level.is_some_and(|level| level >= job.job.level_cap()))
+                .count();
+            let highest = snapshot
+                .jobs
+                .values()
+                .filter_map(|job| Some((job.level?, job.job)))
+                .max_by_key(|&(level, job)| (level, std::cmp::Reverse(job)))
+                .map_or("–".to_string(), |(level, job)| format!("{level} ({job})"));
+            body.push_str(&format!(
+                "<tr><td class=\"number\">{}</td><td>{}</td><td class=\"number\">{capped}</td>\
+                <td>{highest}</td><td class=\"number\">{}</td></tr>\n",
+                rank + 1,
+                character_link(snapshot),
+                total(snapshot),
+            ));
+        }
+        body.push_str("</table>\n");
+    }
+    layout("Leaderboard", "", "", &body, generated_at)
+}
+
+fn character_link(snapshot: &StoredSnapshot) -> String {
+    format!(
+        "<a href=\"{}\">{}</a> <small>{}</small>",
+        character_path(snapshot.user_id).display(),
+        escape(&snapshot.fields.name),
+        escape(&snapshot.fields.server),
+    )
+}
+
+fn layout(title: &str, root: &str, head: &str, body: &str, generated_at: DateTime<Utc>) -> String {
+    format!(
+        "<!DOCTYPE html>
+<html lang=\"en\">
+<head>
+<meta charset=\"utf-8\">
+<title>{} - ffxiv-tracker</title>
+{head}<style>
+{}{STYLE}</style>
+</head>
+<body>
+<nav><a href=\"{root}index.html\">Characters</a><a href=\"{root}leaderboard.html\">Leaderboard</a></nav>
+<p id=\"generated\">Generated {}</p>
+{body}</body>
+</html>
+",
+        escape(title),
+        render::STYLE,
+        generated_at.to_rfc3339(),
+    )
+}
+
+/// Escape text for HTML content and attribute values.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::db::test::{day, test_database, test_profile};
+
+    #[test]
+    fn generate_site() -> Result<(), Error> {
+        let mut database = test_database();
+        let mut profile = test_profile(DateTime::UNIX_EPOCH);
+        profile.name = "<b>Test</b> Player".to_string();
+        profile.free_company = Some("Test Company".to_string());
+        profile.free_company_id = Some(9232379236109629819);
+        database.snapshot(profile)?;
+        let mut profile = test_profile(day(1));
+        profile.name = "<b>Test</b> Player".to_string();
+        profile.free_company = Some("Test Company".to_string());
+        profile.free_company_id = Some(9232379236109629819);
+        if let Some(paladin) = profile.jobs.0.get_mut(&Job::Paladin) {
+            paladin.level = Some(2);
+            paladin.exp = Some((0, 600));
+        }
+        database.snapshot(profile)?;
+
+        let out_dir =
+            std::env::temp_dir().join(format!("ffxiv-tracker-site-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&out_dir);
+        let read = |path: &str| std::fs::read_to_string(out_dir.join(path)).unwrap();
+
+        let first = generate(&database, &out_dir, DateTime::UNIX_EPOCH, false)?;
+        let index = read("index.html");
+        let character = read("characters/1.html");
+        let leaderboard = read("leaderboard.html");
+        let second = generate(&database, &out_dir, DateTime::UNIX_EPOCH, false)?;
+        let forced = generate(&database, &out_dir, DateTime::UNIX_EPOCH, true)?;
+        std::fs::remove_dir_all(&out_dir).unwrap();
+
+        assert_eq!(first.characters, vec![1]);
+        assert!(index.contains("&lt;b&gt;Test&lt;/b&gt; Player"));
+        assert!(!index.contains("<b>Test"));
+        assert!(index.contains("href=\"characters/1.html\""));
+        assert!(character.contains("<td>1970-01-02</td><td>Paladin</td><td>1 → 2</td>"));
+        assert!(character.contains("function drawCharts"));
+        assert!(leaderboard.contains("<h2>Test Company</h2>"));
+        // 300 experience to reach level 2 on Paladin, and none on other jobs
+        assert!(leaderboard.contains("<td class=\"number\">300</td>"));
+
+        assert_eq!(
+            second,
+            Generated {
+                characters: vec![],
+                unchanged: 1,
+            }
+        );
+        assert_eq!(forced.characters, vec![1]);
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+
+use crate::db::{ExperienceRecord, TrackerDatabase};
+use crate::error::Error;
+use crate::experience;
+use crate::job::{Job, JobSnapshot};
+use chrono::{DateTime, TimeDelta, Utc};
+
+/// Leveling progress of a job over a window of time.
+#[derive(Debug, PartialEq)]
+pub struct JobRate {
+    /// The job as of the latest snapshot.
+    pub current: JobSnapshot,
+    pub exp_gained: u64,
+    pub levels_gained: u64,
+    /// Time covered by the measurement, which may be shorter than the window
+    /// if the job wasn't tracked for all of it.
+    pub elapsed: TimeDelta,
+}
+
+impl JobRate {
+    pub fn exp_per_hour(&self) -> Option<f64> {
+        self.per(TimeDelta::hours(1), self.exp_gained)
+    }
+
+    pub fn exp_per_day(&self) -> Option<f64> {
+        self.per(TimeDelta::days(1), self.exp_gained)
+    }
+
+    pub fn levels_per_week(&self) -> Option<f64> {
+        self.per(TimeDelta::weeks(1), self.levels_gained)
+    }
+
+    /// Experience still required to reach the level cap.
+    pub fn exp_to_cap(&self) -> Option<u64> {
+        let cap = experience::exp_to_reach(self.current.job.level_cap())?;
+        Some(cap.saturating_sub(self.current.total_exp()?))
+    }
+
+    /// Projected time at which the job reaches the level cap, if leveling
+    /// continues at the current rate.
+    pub fn eta(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let remaining = self.exp_to_cap()?;
+        if remaining == 0 {
+            return Some(now);
+        }
+        if self.exp_gained == 0 || self.elapsed <= TimeDelta::zero() {
+            return None;
+        }
+        let elapsed = u128::try_from(self.elapsed.num_seconds()).ok()?;
+        let seconds = (u128::from(remaining) * elapsed).div_ceil(u128::from(self.exp_gained));
+        now.checked_add_signed(TimeDelta::try_seconds(i64::try_from(seconds).ok()?)?)
+    }
+
+    fn per(&self, unit: TimeDelta, amount: u64) -> Option<f64> {
+        if self.elapsed <= TimeDelta::zero() {
+            return None;
+        }
+        let units = self.elapsed.num_seconds() as f64 / unit.num_seconds() as f64;
+        Some(amount as f64 / units)
+    }
+}
+
+/// Leveling rates of each unlocked job of a player over the `window` ending at
+/// `now`.
+///
+/// Progress is measured from the job's state at the start of the window (the
+/// latest snapshot before it), or from its first snapshot within the window.
+pub fn rates(
+    database: &TrackerDatabase,
+    user_id: u64,
+    window: TimeDelta,
+    now: DateTime<Utc>,
+) -> Result<BTreeMap<Job, JobRate>, Error> {
+    let start = now - window;
+    let before = database.history(user_id, None, None, Some(start))?;
+    let during = database.history(user_id, None, Some(start), Some(now))?;
+
+    let mut rates: BTreeMap<Job, JobRate> = BTreeMap::new();
+    for (job, records) in during {
+        let baseline = match before.get(&job).and_then(|records| records.last()) {
+            Some(record) => (start, record),
+            None => match records.first() {
+                Some(record) => (record.captured_at.unwrap_or(start), record),
+                None => continue,
+            },
+        };
+        let Some(latest) = records.last() else {
+            continue;
+        };
+        if let Some(rate) = job_rate(job, baseline, latest, now) {
+            rates.insert(job, rate);
+        }
+    }
+    Ok(rates)
+}
+
+fn job_rate(
+    job: Job,
+    (since, baseline): (DateTime<Utc>, &ExperienceRecord),
+    latest: &ExperienceRecord,
+    now: DateTime<Utc>,
+) -> Option<JobRate> {
+    let snapshot = |record: &ExperienceRecord| JobSnapshot {
+        job,
+        level: record.level,
+        exp: record.exp,
+    };
+    let current = snapshot(latest);
+    let current_total = current.total_exp()?;
+    // jobs unlocked during the window have no baseline, since some jobs start
+    // above level 1
+    let (baseline_total, baseline_level) = match snapshot(baseline).total_exp() {
+        Some(total) => (total, baseline.level.unwrap_or_default()),
+        None => (current_total, current.level.unwrap_or_default()),
+    };
+
+    Some(JobRate {
+        exp_gained: current_total.saturating_sub(baseline_total),
+        levels_gained: current
+            .level
+            .unwrap_or_default()
+            .saturating_sub(baseline_level),
+        elapsed: now - since,
+        current,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::db::test::{day, test_database, test_profile};
+
+    #[test]
+    fn rate_and_eta() {
+        let now = day(10);
+        let rate = JobRate {
+            current: JobSnapshot {
+                job: Job::BlueMage,
+                level: Some(79),
+                exp: Some((0, experience::EXP_TO_NEXT[78])),
+            },
+            exp_gained: 48_000,
+            levels_gained: 1,
+            elapsed: TimeDelta::days(2),
+        };
+
+        assert_eq!(rate.exp_per_hour(), Some(1_000.0));
+        assert_eq!(rate.exp_per_day(), Some(24_000.0));
+        assert_eq!(rate.levels_per_week(), Some(3.5));
+
+        let remaining = experience::EXP_TO_NEXT[78];
+        assert_eq!(rate.exp_to_cap(), Some(remaining));
+        let hours = (remaining as f64 / 1_000.0).ceil() as i64;
+        assert_eq!(rate.eta(now), Some(now + TimeDelta::hours(hours)));
+
+        let stalled = JobRate {
+            exp_gained: 0,
+            ..rate
+        };
+        assert_eq!(stalled.eta(now), None);
+    }
+
+    #[test]
+    fn rates_from_snapshots() -> Result<(), Error> {
+        let mut database = test_database();
+        database.snapshot(test_profile(day(0)))?;
+        let mut profile = test_profile(day(2));
+        if let Some(paladin) = profile.jobs.0.get_mut(&Job::Paladin) {
+            paladin.level = Some(2);
+            paladin.exp = Some((100, 600));
+        }
+        database.snapshot(profile)?;
+
+        // the window covers both snapshots, so progress starts at the first
+        let weekly = rates(&database, 1, TimeDelta::days(7), day(3))?;
+        let paladin = &weekly[&Job::Paladin];
+        assert_eq!(paladin.exp_gained, 400);
+        assert_eq!(paladin.levels_gained, 1);
+        assert_eq!(paladin.elapsed, TimeDelta::days(3));
+        assert_eq!(paladin.current.level, Some(2));
+        assert_eq!(weekly[&Job::Warrior].exp_gained, 0);
+
+        // the window starts between them, so progress starts at its start
+        let recent = rates(&database, 1, TimeDelta::days(2), day(3))?;
+        assert_eq!(recent[&Job::Paladin].exp_gained, 400);
+        assert_eq!(recent[&Job::Paladin].elapsed, TimeDelta::days(2));
+
+        // nothing is measured without snapshots in the window
+        assert!(rates(&database, 1, TimeDelta::hours(1), day(3))?.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+// Draws level and total experience charts per character, with a toggle per
+// job shared by every chart. Shared by the rendered and standalone pages.
+//
+// `data` is shaped like:
+//   { generated_at (optional), roles: [{ role, color }],
+//     characters: [{ user_id, name, server,
+//                    jobs: [{ job, role, color, points: [{ t, level, total_xp }] }] }] }
+let charts = [];
+
+const day = (ms) => new Date(ms).toISOString().slice(0, 10);
+
+function chart(canvas, character, metric, title) {
+  const datasets = character.jobs.map((job) => ({
+    label: job.job,
+    borderColor: job.color,
+    backgroundColor: job.color,
+    data: job.points
+      .filter((point) => point[metric] !== null)
+      .map((point) => ({ x: point.t, y: point[metric] })),
+  }));
+  return new Chart(canvas, {
+    type: "line",
+    data: { datasets },
+    options: {
+      scales: {
+        x: { type: "linear", ticks: { callback: day } },
+        y: { title: { display: true, text: title } },
+      },
+      plugins: {
+        legend: { display: false },
+        tooltip: { callbacks: { title: (items) => day(items[0].parsed.x) } },
+      },
+    },
+  });
+}
+
+function drawCharts(data) {
+  for (const old of charts) {
+    old.destroy();
+  }
+  charts = [];
+  const container = document.getElementById("characters");
+  const toggles = document.getElementById("toggles");
+  container.replaceChildren();
+  toggles.replaceChildren();
+  document.getElementById("generated").textContent = data.generated_at
+    ? `Generated ${data.generated_at}`
+    : "";
+
+  for (const character of data.characters) {
+    const section = document.createElement("section");
+    const heading = document.createElement("h2");
+    heading.textContent = `${character.name} (${character.server})`;
+    const grid = document.createElement("div");
+    grid.className = "charts";
+    section.append(heading, grid);
+    container.append(section);
+
+    for (const [metric, title] of [["level", "Level"], ["total_xp", "Total XP"]]) {
+      const canvas = document.createElement("canvas");
+      const wrapper = document.createElement("div");
+      wrapper.append(canvas);
+      grid.append(wrapper);
+      charts.push(chart(canvas, character, metric, title));
+    }
+  }
+
+  // one toggle per job, shared by every chart, grouped by role
+  for (const role of data.roles) {
+    const jobs = [];
+    for (const character of data.characters) {
+      for (const job of character.jobs) {
+        if (job.role === role.role && !jobs.some((other) => other.job === job.job)) {
+          jobs.push(job);
+        }
+      }
+    }
+    if (jobs.length === 0) {
+      continue;
+    }
+
+    const fieldset = document.createElement("fieldset");
+    const legend = document.createElement("legend");
+    legend.textContent = role.role;
+    legend.style.color = role.color;
+    fieldset.append(legend);
+    for (const job of jobs) {
+      const label = document.createElement("label");
+      const checkbox = document.createElement("input");
+      checkbox.type = "checkbox";
+      checkbox.checked = true;
+      checkbox.addEventListener("change", () => {
+        for (const chart of charts) {
+          for (const dataset of chart.data.datasets) {
+            if (dataset.label === job.job) {
+              dataset.hidden = !checkbox.checked;
+            }
+          }
+          chart.update();
+        }
+      });
+      const swatch = document.createElement("span");
+      swatch.className = "swatch";
+      swatch.style.background = job.color;
+      label.append(checkbox, swatch, job.job);
+      fieldset.append(label);
+    }
+    toggles.append(fieldset);
+  }
+}
@@ -4,7 +4,8 @@ use std::path::{Path, PathBuf};
 use clap::{Parser, Subcommand};
 use tracing::{event, info_span, instrument, Level};
 
-const PROFILE_BASE_URL: &str = "https://na.finalfantasyxiv.com/lodestone/character/";
+use lib::region::Region;
+
 const PROFILES: &str = include_str!("tests/test-profiles.kdl");
 
 #[derive(Debug, knuffel::Decode)]
@@ -13,18 +14,21 @@ struct TestProfile {
     name: String,
     #[knuffel(property)]
     id: u64,
+    #[knuffel(property, str, default)]
+    region: Region,
 }
 
 impl TestProfile {
     #[instrument(skip_all)]
     fn fetch(self, output_directory: &Path) -> Result<(), String> {
-        let TestProfile { name, id } = &self;
+        let TestProfile { name, id, region } = &self;
 
         let profile = output_directory.join(PathBuf::from(format!("{name}_profile.html")));
         let job = output_directory.join(PathBuf::from(format!("{name}_jobs.html")));
 
-        let profile_url = format!("{0}/{id}", PROFILE_BASE_URL);
-        let job_url = format!("{0}/{id}/class_job", PROFILE_BASE_URL);
+        let base_url = region.character_url();
+        let profile_url = format!("{base_url}/{id}");
+        let job_url = format!("{base_url}/{id}/class_job");
 
         let (profile_html, job_html) = info_span!("download").in_scope(|| {
             let profile_html = ureq::get(profile_url.as_str())